@@ -0,0 +1,82 @@
+//! Benchmarks for the assets module.
+
+#![cfg(feature = "runtime-benchmarks")]
+
+use super::*;
+use frame_benchmarking::{account, benchmarks, whitelisted_caller};
+use frame_system::RawOrigin;
+
+const SEED: u32 = 0;
+const SUPPLY: u32 = 1_000_000;
+const AMOUNT: u32 = 1_000;
+
+const BENCH_TOKEN: &AssetInfo = &AssetInfo {
+    name: *b"zenlinkbenchmark",
+    symbol: *b"BENCH___",
+    decimals: 0u8,
+};
+
+benchmarks! {
+    _ { }
+
+    issue {
+        let caller: T::AccountId = whitelisted_caller();
+        let id = Module::<T>::next_asset_id();
+    }: _(RawOrigin::Signed(caller.clone()), SUPPLY.into(), BENCH_TOKEN.clone())
+    verify {
+        assert_eq!(Module::<T>::balance_of(&id, &caller), SUPPLY.into());
+    }
+
+    transfer {
+        let caller: T::AccountId = whitelisted_caller();
+        let target: T::AccountId = account("target", 0, SEED);
+        let id = Module::<T>::inner_issue(&caller, SUPPLY.into(), BENCH_TOKEN);
+    }: _(RawOrigin::Signed(caller), id, T::Lookup::unlookup(target.clone()), AMOUNT.into())
+    verify {
+        assert_eq!(Module::<T>::balance_of(&id, &target), AMOUNT.into());
+    }
+
+    approve {
+        let caller: T::AccountId = whitelisted_caller();
+        let spender: T::AccountId = account("spender", 0, SEED);
+        let id = Module::<T>::inner_issue(&caller, SUPPLY.into(), BENCH_TOKEN);
+    }: _(RawOrigin::Signed(caller.clone()), id, T::Lookup::unlookup(spender.clone()), AMOUNT.into())
+    verify {
+        assert_eq!(Module::<T>::allowances(&id, &caller, &spender), AMOUNT.into());
+    }
+
+    transfer_from {
+        let owner: T::AccountId = account("owner", 0, SEED);
+        let caller: T::AccountId = whitelisted_caller();
+        let target: T::AccountId = account("target", 0, SEED);
+        let id = Module::<T>::inner_issue(&owner, SUPPLY.into(), BENCH_TOKEN);
+        Module::<T>::inner_approve(&id, &owner, &caller, AMOUNT.into())?;
+    }: _(
+        RawOrigin::Signed(caller.clone()),
+        id,
+        T::Lookup::unlookup(owner.clone()),
+        T::Lookup::unlookup(target.clone()),
+        AMOUNT.into()
+    )
+    verify {
+        assert_eq!(Module::<T>::balance_of(&id, &target), AMOUNT.into());
+        assert!(Module::<T>::allowances(&id, &owner, &caller).is_zero());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{new_test_ext, Test};
+    use frame_support::assert_ok;
+
+    #[test]
+    fn test_benchmarks() {
+        new_test_ext().execute_with(|| {
+            assert_ok!(test_benchmark_issue::<Test>());
+            assert_ok!(test_benchmark_transfer::<Test>());
+            assert_ok!(test_benchmark_approve::<Test>());
+            assert_ok!(test_benchmark_transfer_from::<Test>());
+        });
+    }
+}
@@ -0,0 +1,37 @@
+//! Default weights of the assets module.
+//!
+//! NOTE: these are placeholders estimated by hand from the storage accesses of every call, they are
+//! not benchmark output and must be replaced by the `WeightInfo` impl generated from
+//! `src/benchmarking.rs` before they are relied on. Generate them on the reference hardware by a
+//! node built with the `runtime-benchmarks` feature:
+//!
+//! ```text
+//! node benchmark --chain dev --execution wasm --wasm-execution compiled \
+//!     --pallet zenlink_assets --extrinsic '*' --steps 50 --repeat 20 \
+//!     --output assets/src/default_weights.rs
+//! ```
+
+use frame_support::weights::{constants::RocksDbWeight as DbWeight, Weight};
+
+impl crate::WeightInfo for () {
+    fn issue() -> Weight {
+        (38_000_000 as Weight)
+            .saturating_add(DbWeight::get().reads(1 as Weight))
+            .saturating_add(DbWeight::get().writes(4 as Weight))
+    }
+    fn transfer() -> Weight {
+        (42_000_000 as Weight)
+            .saturating_add(DbWeight::get().reads(3 as Weight))
+            .saturating_add(DbWeight::get().writes(2 as Weight))
+    }
+    fn approve() -> Weight {
+        (26_000_000 as Weight)
+            .saturating_add(DbWeight::get().reads(1 as Weight))
+            .saturating_add(DbWeight::get().writes(1 as Weight))
+    }
+    fn transfer_from() -> Weight {
+        (56_000_000 as Weight)
+            .saturating_add(DbWeight::get().reads(4 as Weight))
+            .saturating_add(DbWeight::get().writes(3 as Weight))
+    }
+}
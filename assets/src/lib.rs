@@ -2,8 +2,12 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 use codec::{Decode, Encode};
-use frame_support::{decl_error, decl_event, decl_module, decl_storage, ensure, Parameter};
+use frame_support::{
+    decl_error, decl_event, decl_module, decl_storage, ensure, transactional, traits::Get, weights::Weight, Parameter,
+};
 use frame_system::ensure_signed;
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
 use sp_runtime::{DispatchResult, RuntimeDebug};
 use sp_runtime::traits::{
     AtLeast32Bit, AtLeast32BitUnsigned, CheckedSub, MaybeSerializeDeserialize, Member, One, Saturating, StaticLookup,
@@ -14,6 +18,10 @@ use sp_runtime::traits::{
 mod mock;
 #[cfg(test)]
 mod tests;
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+mod default_weights;
+mod migration;
 
 /// the symbol of asset.
 type Symbol = [u8; 8];
@@ -21,12 +29,36 @@ type Symbol = [u8; 8];
 type Name = [u8; 16];
 
 #[derive(Encode, Decode, Eq, PartialEq, Clone, RuntimeDebug, Default)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
 pub struct AssetInfo {
     pub name: Name,
     pub symbol: Symbol,
     pub decimals: u8,
 }
 
+/// The storage layout versions of the assets module
+#[derive(Encode, Decode, Eq, PartialEq, Clone, Copy, RuntimeDebug)]
+pub enum Releases {
+    /// The balances and allowances in maps keyed by the tuples with the asset id.
+    V1,
+    /// The balances and allowances in double maps with the asset id as the first key, so the
+    /// entries of an asset can be removed by prefix.
+    V2,
+}
+
+impl Default for Releases {
+    fn default() -> Self {
+        Releases::V1
+    }
+}
+
+pub trait WeightInfo {
+    fn issue() -> Weight;
+    fn transfer() -> Weight;
+    fn approve() -> Weight;
+    fn transfer_from() -> Weight;
+}
+
 /// The module configuration trait.
 pub trait Trait: frame_system::Trait {
     /// The overarching event type.
@@ -37,22 +69,36 @@ pub trait Trait: frame_system::Trait {
 
     /// The arithmetic type of asset identifier.
     type AssetId: Parameter + AtLeast32Bit + Default + Copy + MaybeSerializeDeserialize;
+
+    /// Weight information for extrinsics in this pallet.
+    type WeightInfo: WeightInfo;
 }
 
-// TODO: weight
-// TODO: transaction
 decl_module! {
     pub struct Module<T: Trait> for enum Call where origin: T::Origin {
         type Error = Error<T>;
 
         fn deposit_event() = default;
+
+        fn on_runtime_upgrade() -> Weight {
+            if Self::storage_version() == Releases::V1 {
+                let weight = Self::migrate_to_v2();
+                StorageVersion::put(Releases::V2);
+
+                weight.saturating_add(T::DbWeight::get().reads_writes(1, 1))
+            } else {
+                T::DbWeight::get().reads(1)
+            }
+        }
+
         /// Issue a new class of pallet-zenlink assets. There are, and will only ever be, `total`
         /// such assets and they'll all belong to the `origin` initially. It will have an
         /// identifier `AssetId` instance: this will be specified in the `Issued` event.
         ///
         /// - `total`: initial total supply.
         /// - `asset_info`: the asset info contains `name`, `symbol`, `decimals`.
-        #[weight = 0]
+        #[weight = T::WeightInfo::issue()]
+        #[transactional]
         fn issue(origin, #[compact] total: T::TokenBalance, asset_info: AssetInfo) {
             let origin = ensure_signed(origin)?;
             Self::inner_issue(&origin, total, &asset_info);
@@ -63,7 +109,8 @@ decl_module! {
         /// - `id`: the asset id.
         /// - `target`: the receiver of the asset.
         /// - `amount`: the amount of the asset to transfer.
-        #[weight = 0]
+        #[weight = T::WeightInfo::transfer()]
+        #[transactional]
         fn transfer(origin,
             #[compact] id: T::AssetId,
             target: <T::Lookup as StaticLookup>::Source,
@@ -80,7 +127,8 @@ decl_module! {
         /// - `id`: the asset id.
         /// - `spender`: the spender account.
         /// - `amount`: the amount of allowance.
-        #[weight = 0]
+        #[weight = T::WeightInfo::approve()]
+        #[transactional]
         fn approve(origin,
             #[compact] id: T::AssetId,
             spender: <T::Lookup as StaticLookup>::Source,
@@ -98,7 +146,8 @@ decl_module! {
         /// - `from`: the source of the asset to be transferred.
         /// - `target`: the receiver of the asset to be transferred.
         /// - `amount`: the amount of asset to be transferred.
-        #[weight = 0]
+        #[weight = T::WeightInfo::transfer_from()]
+        #[transactional]
         fn transfer_from(origin,
             #[compact] id: T::AssetId,
             from: <T::Lookup as StaticLookup>::Source,
@@ -133,6 +182,8 @@ decl_event! {
         Burned(AssetId, AccountId, TokenBalance),
         /// Some assets were minted. \[asset_id, owner, amount\]
         Minted(AssetId, AccountId, TokenBalance),
+        /// An asset was destroyed. \[asset_id\]
+        Destroyed(AssetId),
     }
 }
 
@@ -155,16 +206,49 @@ decl_storage! {
     trait Store for Module<T: Trait> as Assets {
         /// The info of the asset by any given asset id.
         AssetInfos: map hasher(twox_64_concat) T::AssetId => Option<AssetInfo>;
-        /// The number of units of assets held by any given account.
-        Balances: map hasher(blake2_128_concat) (T::AssetId, T::AccountId) => T::TokenBalance;
+        /// The number of units of assets held by any given account: asset_id, account -> balance
+        Balances: double_map hasher(twox_64_concat) T::AssetId, hasher(blake2_128_concat) T::AccountId => T::TokenBalance;
         /// The next asset identifier up for grabs.
         NextAssetId get(fn next_asset_id): T::AssetId;
         /// The total unit supply of an asset.
         ///
         /// TWOX-NOTE: `AssetId` is trusted, so this is safe.
         TotalSupply: map hasher(twox_64_concat) T::AssetId => T::TokenBalance;
-        /// The allowance of assets held by spender who can spend from owner.
-        Allowances: map hasher(blake2_128_concat) (T::AssetId, T::AccountId, T::AccountId) => T::TokenBalance;
+        /// The allowance of assets held by spender who can spend from owner: asset_id, (owner, spender) -> allowance
+        Allowances: double_map hasher(twox_64_concat) T::AssetId, hasher(blake2_128_concat) (T::AccountId, T::AccountId) => T::TokenBalance;
+        /// The storage layout version, new chains start with the latest one.
+        StorageVersion get(fn storage_version) build(|_| Releases::V2): Releases;
+    }
+    add_extra_genesis {
+        /// The assets issued at genesis, their ids are assigned by order: (owner, initial_supply, asset_info)
+        config(assets): Vec<(T::AccountId, T::TokenBalance, AssetInfo)>;
+        /// The extra balances minted at genesis: (asset_id, account, amount)
+        config(balances): Vec<(T::AssetId, T::AccountId, T::TokenBalance)>;
+        /// The allowances at genesis: (asset_id, owner, spender, amount)
+        config(allowances): Vec<(T::AssetId, T::AccountId, T::AccountId, T::TokenBalance)>;
+        build(|config: &GenesisConfig<T>| {
+            for (owner, initial_supply, asset_info) in config.assets.iter() {
+                let id = <NextAssetId<T>>::get();
+                <NextAssetId<T>>::put(id + One::one());
+
+                <Balances<T>>::insert(id, owner, initial_supply);
+                <TotalSupply<T>>::insert(id, initial_supply);
+                <AssetInfos<T>>::insert(id, asset_info);
+            }
+
+            for (id, who, amount) in config.balances.iter() {
+                assert!(<AssetInfos<T>>::contains_key(id), "Asset of the genesis balance does not exist");
+
+                <Balances<T>>::mutate(id, who, |balance| *balance = balance.saturating_add(*amount));
+                <TotalSupply<T>>::mutate(id, |supply| *supply = supply.saturating_add(*amount));
+            }
+
+            for (id, owner, spender, amount) in config.allowances.iter() {
+                assert!(<AssetInfos<T>>::contains_key(id), "Asset of the genesis allowance does not exist");
+
+                <Allowances<T>>::insert(id, (owner, spender), amount);
+            }
+        });
     }
 }
 
@@ -183,7 +267,7 @@ impl<T: Trait> Module<T> {
         let id = Self::next_asset_id();
         <NextAssetId<T>>::mutate(|id| *id += One::one());
 
-        <Balances<T>>::insert((id, owner), initial_supply);
+        <Balances<T>>::insert(id, owner, initial_supply);
         <TotalSupply<T>>::insert(id, initial_supply);
         <AssetInfos<T>>::insert(id, info);
 
@@ -199,14 +283,15 @@ impl<T: Trait> Module<T> {
         target: &T::AccountId,
         amount: T::TokenBalance,
     ) -> DispatchResult {
-        let owner_balance = <Balances<T>>::get((id, owner));
+        let owner_balance = <Balances<T>>::get(id, owner);
         ensure!(!amount.is_zero(), Error::<T>::AmountZero);
+        ensure!(Self::asset_info(id).is_some(), Error::<T>::AssetNotExists);
         ensure!(owner_balance >= amount, Error::<T>::BalanceLow);
 
         let new_balance = owner_balance.saturating_sub(amount);
 
-        <Balances<T>>::mutate((id, owner), |balance| *balance = new_balance);
-        <Balances<T>>::mutate((id, target), |balance| {
+        <Balances<T>>::mutate(id, owner, |balance| *balance = new_balance);
+        <Balances<T>>::mutate(id, target, |balance| {
             *balance = balance.saturating_add(amount)
         });
 
@@ -227,7 +312,7 @@ impl<T: Trait> Module<T> {
         spender: &T::AccountId,
         amount: T::TokenBalance,
     ) -> DispatchResult {
-        <Allowances<T>>::mutate((id, owner, spender), |balance| *balance = amount);
+        <Allowances<T>>::mutate(id, (owner, spender), |balance| *balance = amount);
 
         Self::deposit_event(RawEvent::Approval(
             *id,
@@ -247,25 +332,23 @@ impl<T: Trait> Module<T> {
         target: &T::AccountId,
         amount: T::TokenBalance,
     ) -> DispatchResult {
-        let allowance = <Allowances<T>>::get((id, owner, spender));
+        let allowance = <Allowances<T>>::get(id, (owner, spender));
         let new_balance = allowance
             .checked_sub(&amount)
             .ok_or(Error::<T>::AllowanceLow)?;
 
-        Self::inner_transfer(&id, &owner, &target, amount)?;
+        <Allowances<T>>::mutate(id, (owner, spender), |balance| *balance = new_balance);
 
-        <Allowances<T>>::mutate((id, owner, spender), |balance| *balance = new_balance);
-
-        Ok(())
+        Self::inner_transfer(&id, &owner, &target, amount)
     }
 
     /// Increase the total supply of the asset
     pub fn inner_mint(id: &T::AssetId, owner: &T::AccountId, amount: T::TokenBalance) -> DispatchResult {
         ensure!(Self::asset_info(id).is_some(), Error::<T>::AssetNotExists);
 
-        let new_balance = <Balances<T>>::get((id, owner)).saturating_add(amount);
+        let new_balance = <Balances<T>>::get(id, owner).saturating_add(amount);
 
-        <Balances<T>>::mutate((id, owner), |balance| *balance = new_balance);
+        <Balances<T>>::mutate(id, owner, |balance| *balance = new_balance);
         <TotalSupply<T>>::mutate(id, |supply| {
             *supply = supply.saturating_add(amount);
         });
@@ -279,11 +362,11 @@ impl<T: Trait> Module<T> {
     pub fn inner_burn(id: &T::AssetId, owner: &T::AccountId, amount: T::TokenBalance) -> DispatchResult {
         ensure!(Self::asset_info(id).is_some(), Error::<T>::AssetNotExists);
 
-        let new_balance = <Balances<T>>::get((id, owner))
+        let new_balance = <Balances<T>>::get(id, owner)
             .checked_sub(&amount)
             .ok_or(Error::<T>::BalanceLow)?;
 
-        <Balances<T>>::mutate((id, owner), |balance| *balance = new_balance);
+        <Balances<T>>::mutate(id, owner, |balance| *balance = new_balance);
         <TotalSupply<T>>::mutate(id, |supply| {
             *supply = supply.saturating_sub(amount);
         });
@@ -293,11 +376,25 @@ impl<T: Trait> Module<T> {
         Ok(())
     }
 
+    /// Remove the info, total supply, balances and allowances of the asset.
+    pub fn inner_destroy(id: &T::AssetId) -> DispatchResult {
+        ensure!(Self::asset_info(id).is_some(), Error::<T>::AssetNotExists);
+
+        <AssetInfos<T>>::remove(id);
+        <TotalSupply<T>>::remove(id);
+        <Balances<T>>::remove_prefix(id);
+        <Allowances<T>>::remove_prefix(id);
+
+        Self::deposit_event(RawEvent::Destroyed(*id));
+
+        Ok(())
+    }
+
     // Public immutable functions
 
     /// Get the asset `id` balance of `owner`.
     pub fn balance_of(id: &T::AssetId, owner: &T::AccountId) -> T::TokenBalance {
-        <Balances<T>>::get((id, owner))
+        <Balances<T>>::get(id, owner)
     }
 
     /// Get the total supply of an asset `id`.
@@ -307,7 +404,7 @@ impl<T: Trait> Module<T> {
 
     /// Get the allowance balance of the spender under owner
     pub fn allowances(id: &T::AssetId, owner: &T::AccountId, spender: &T::AccountId) -> T::TokenBalance {
-        <Allowances<T>>::get((id, owner, spender))
+        <Allowances<T>>::get(id, (owner, spender))
     }
 
     /// Get the info of the asset by th asset `id`
@@ -0,0 +1,36 @@
+//! Storage migrations of the assets module.
+
+use frame_support::{sp_std::vec::Vec, storage::migration::StorageKeyIterator, weights::Weight, Blake2_128Concat};
+
+use super::*;
+
+const MODULE: &[u8] = b"Assets";
+
+impl<T: Trait> Module<T> {
+    /// Move the balances and allowances keyed by the tuples to the double maps keyed by the asset
+    /// id first. The old entries are drained before the new ones are written under the same prefix.
+    pub(crate) fn migrate_to_v2() -> Weight {
+        let balances = StorageKeyIterator::<(T::AssetId, T::AccountId), T::TokenBalance, Blake2_128Concat>::new(
+            MODULE,
+            b"Balances",
+        )
+        .drain()
+        .collect::<Vec<_>>();
+        let allowances = StorageKeyIterator::<(T::AssetId, T::AccountId, T::AccountId), T::TokenBalance, Blake2_128Concat>::new(
+            MODULE,
+            b"Allowances",
+        )
+        .drain()
+        .collect::<Vec<_>>();
+        let count = (balances.len() + allowances.len()) as Weight;
+
+        for ((id, owner), balance) in balances {
+            <Balances<T>>::insert(id, owner, balance);
+        }
+        for ((id, owner, spender), allowance) in allowances {
+            <Allowances<T>>::insert(id, (owner, spender), allowance);
+        }
+
+        T::DbWeight::get().reads_writes(count, count * 2)
+    }
+}
@@ -53,6 +53,7 @@ impl Trait for Test {
     type Event = ();
     type TokenBalance = u64;
     type AssetId = u32;
+    type WeightInfo = ();
 }
 
 pub type Assets = Module<Test>;
@@ -1,5 +1,11 @@
-use crate::{mock::*, AssetInfo, Error, Name, Symbol};
-use frame_support::{assert_noop, assert_ok};
+use crate::{mock::*, Allowances, AssetInfo, Balances, Error, GenesisConfig, Name, Releases, Symbol};
+use codec::Encode;
+use frame_support::{
+    assert_noop, assert_ok,
+    storage::migration::{get_storage_value, put_storage_value},
+    traits::OnRuntimeUpgrade,
+    Blake2_128Concat, StorageDoubleMap, StorageHasher, StorageValue,
+};
 
 const TEST_ASSET_NAME: Name = *b"zenlink_swap_v_1";
 const TEST_ASSET_SYMBOL: Symbol = *b"zlktest1";
@@ -137,6 +143,25 @@ fn transfer_from_should_not_work() {
     });
 }
 
+#[test]
+fn transfer_from_should_roll_back() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Assets::issue(Origin::signed(1), 100, TEST_ASSET_INFO));
+        assert_ok!(Assets::transfer(Origin::signed(1), 0, 2, 90));
+        assert_ok!(Assets::approve(Origin::signed(1), 0, 2, 20));
+
+        // The allowance is decremented first, then the transfer of the owner's balance fails
+        assert_noop!(
+            Assets::transfer_from(Origin::signed(2), 0, 1, 3, 20),
+            Error::<Test>::BalanceLow
+        );
+
+        assert_eq!(Assets::allowances(&0, &1, &2), 20);
+        assert_eq!(Assets::balance_of(&0, &1), 10);
+        assert_eq!(Assets::balance_of(&0, &3), 0);
+    });
+}
+
 #[test]
 fn inner_mint_should_work() {
     new_test_ext().execute_with(|| {
@@ -172,6 +197,69 @@ fn inner_burn_should_not_work() {
     });
 }
 
+#[test]
+fn inner_destroy_should_work() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Assets::issue(Origin::signed(1), 100, TEST_ASSET_INFO));
+        assert_ok!(Assets::issue(Origin::signed(1), 100, TEST_ASSET_INFO));
+        assert_ok!(Assets::transfer(Origin::signed(1), 0, 2, 50));
+        assert_ok!(Assets::approve(Origin::signed(1), 0, 2, 20));
+        assert_ok!(Assets::approve(Origin::signed(1), 1, 2, 20));
+
+        assert_ok!(Assets::inner_destroy(&0));
+        assert_eq!(Assets::asset_info(&0), None);
+        assert_eq!(Assets::total_supply(&0), 0);
+
+        // The balances and allowances of the asset are removed, the other asset's are kept
+        assert!(!Balances::<Test>::contains_key(0, 1));
+        assert!(!Balances::<Test>::contains_key(0, 2));
+        assert!(!Allowances::<Test>::contains_key(0, (1, 2)));
+        assert_eq!(Assets::balance_of(&1, &1), 100);
+        assert_eq!(Assets::allowances(&1, &1, &2), 20);
+
+        assert_noop!(
+            Assets::inner_destroy(&0),
+            Error::<Test>::AssetNotExists,
+        );
+        assert_noop!(
+            Assets::inner_mint(&0, &1, 100),
+            Error::<Test>::AssetNotExists,
+        );
+        // The balance left can't be transferred
+        assert_noop!(
+            Assets::transfer(Origin::signed(1), 0, 2, 50),
+            Error::<Test>::AssetNotExists,
+        );
+    });
+}
+
+fn put_tuple_keyed<K: Encode>(item: &[u8], key: K, value: u64) {
+    put_storage_value(b"Assets", item, &Blake2_128Concat::hash(&key.encode()), value);
+}
+
+#[test]
+fn migrate_to_v2_should_work() {
+    new_test_ext().execute_with(|| {
+        crate::StorageVersion::put(Releases::V1);
+
+        // The balances and allowances keyed by the tuples
+        put_tuple_keyed(b"Balances", (0u32, 1u64), 100);
+        put_tuple_keyed(b"Balances", (1u32, 2u64), 50);
+        put_tuple_keyed(b"Allowances", (0u32, 1u64, 3u64), 30);
+
+        Assets::on_runtime_upgrade();
+
+        assert_eq!(Assets::storage_version(), Releases::V2);
+        assert_eq!(Assets::balance_of(&0, &1), 100);
+        assert_eq!(Assets::balance_of(&1, &2), 50);
+        assert_eq!(Assets::allowances(&0, &1, &3), 30);
+        assert_eq!(
+            get_storage_value::<u64>(b"Assets", b"Balances", &Blake2_128Concat::hash(&(0u32, 1u64).encode())),
+            None
+        );
+    });
+}
+
 #[test]
 fn inner_mint_transfer_burn_should_work() {
     new_test_ext().execute_with(|| {
@@ -192,4 +280,31 @@ fn inner_mint_transfer_burn_should_work() {
             Error::<Test>::BalanceLow,
         );
     });
-}
\ No newline at end of file
+}
+
+#[test]
+fn genesis_config_should_work() {
+    let storage = GenesisConfig::<Test> {
+        assets: vec![(1, 100, TEST_ASSET_INFO), (2, 200, TEST_ASSET_INFO)],
+        balances: vec![(0, 2, 50), (1, 2, 10)],
+        allowances: vec![(0, 1, 3, 30)],
+    }
+    .build_storage()
+    .unwrap();
+
+    sp_io::TestExternalities::from(storage).execute_with(|| {
+        assert_eq!(Assets::next_asset_id(), 2);
+        assert_eq!(Assets::asset_info(&0), Some(TEST_ASSET_INFO));
+        assert_eq!(Assets::asset_info(&1), Some(TEST_ASSET_INFO));
+
+        assert_eq!(Assets::balance_of(&0, &1), 100);
+        assert_eq!(Assets::balance_of(&0, &2), 50);
+        assert_eq!(Assets::total_supply(&0), 150);
+        assert_eq!(Assets::balance_of(&1, &2), 210);
+        assert_eq!(Assets::total_supply(&1), 210);
+
+        assert_eq!(Assets::allowances(&0, &1, &3), 30);
+        assert_ok!(Assets::transfer_from(Origin::signed(3), 0, 1, 3, 30));
+        assert_eq!(Assets::balance_of(&0, &3), 30);
+    });
+}
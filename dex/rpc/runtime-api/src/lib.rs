@@ -3,13 +3,22 @@
 #![allow(clippy::unnecessary_mut_passed)]
 
 use codec::Codec;
+use sp_runtime::FixedU128;
 use sp_std::vec::Vec;
 
-use zenlink_dex::{ExchangeInfo, TokenInfo};
+use zenlink_dex::{
+    AmountQuote, AssetBalance, ExchangeFilter, ExchangeInfo, ExchangeInfoV1, ExchangePage, LiquidityPosition, SwapAsset, SwapKind,
+    SwapRoute, TokenInfo,
+};
 
 // Here we declare the runtime API. It is implemented it the `impl` block in
 // runtime amalgamator file (the `runtime/src/lib.rs`)
 sp_api::decl_runtime_apis! {
+    /// Version 2 added `get_average_price`, `get_amount_out`, `get_amount_in`, `get_exchanges_paged`,
+    /// `get_liquidity_position`, `get_liquidity_positions` and `find_best_route`, and changed
+    /// `ExchangeInfo` to hold the fee rate and the reserves in the exchange. The exchanges of
+    /// version 1 are returned by the `*_before_version_2` calls as `ExchangeInfoV1`.
+    #[api_version(2)]
     pub trait ZenlinkDexApi<AccountId, AssetId, TokenBalance, Balance, ExchangeId>
     where
         AccountId: Codec,
@@ -21,9 +30,53 @@ sp_api::decl_runtime_apis! {
         fn get_token_info(token_id: AssetId) -> Option<TokenInfo<TokenBalance>>;
         fn get_token_balance(token_id: AssetId, owner: AccountId) -> TokenBalance;
         fn get_token_allowance(token_id: AssetId, owner: AccountId, spender: AccountId) -> TokenBalance;
+        #[changed_in(2)]
+        fn get_exchange_by_token_id(token_id: AssetId) -> Option<ExchangeInfoV1<AccountId, AssetId, TokenBalance, Balance, ExchangeId>>;
         fn get_exchange_by_token_id(token_id: AssetId) -> Option<ExchangeInfo<AccountId, AssetId, TokenBalance, Balance, ExchangeId>>;
+        #[changed_in(2)]
+        fn get_exchange_by_id(id: ExchangeId) -> Option<ExchangeInfoV1<AccountId, AssetId, TokenBalance, Balance, ExchangeId>>;
         fn get_exchange_by_id(id: ExchangeId) -> Option<ExchangeInfo<AccountId, AssetId, TokenBalance, Balance, ExchangeId>>;
-        // TODO：Pagination
+        #[changed_in(2)]
+        fn get_exchanges() -> Vec<ExchangeInfoV1<AccountId, AssetId, TokenBalance, Balance, ExchangeId>>;
+        /// All exchanges ordered by id, `get_exchanges_paged` is preferred for many exchanges.
         fn get_exchanges() -> Vec<ExchangeInfo<AccountId, AssetId, TokenBalance, Balance, ExchangeId>>;
+        /// The page of the exchanges matching the filter, from the cursor on, ordered by id.
+        ///
+        /// Added in version 2.
+        fn get_exchanges_paged(
+            cursor: Option<ExchangeId>,
+            limit: u32,
+            filter: ExchangeFilter<AssetId, Balance>,
+        ) -> ExchangePage<AccountId, AssetId, TokenBalance, Balance, ExchangeId>;
+        /// The time-weighted average prices of the exchange or pair over at least the last `window` blocks.
+        ///
+        /// Added in version 2.
+        fn get_average_price(exchange_id: ExchangeId, window: u32) -> Option<(FixedU128, FixedU128)>;
+        /// The output amount and price impact of selling the exact input amount.
+        ///
+        /// Added in version 2.
+        fn get_amount_out(asset_in: SwapAsset<AssetId>, asset_out: SwapAsset<AssetId>, amount_in: AssetBalance) -> Option<AmountQuote<AssetBalance>>;
+        /// The input amount and price impact of buying the exact output amount.
+        ///
+        /// Added in version 2.
+        fn get_amount_in(asset_in: SwapAsset<AssetId>, asset_out: SwapAsset<AssetId>, amount_out: AssetBalance) -> Option<AmountQuote<AssetBalance>>;
+        /// The liquidity token balance of the owner in the exchange, its share and underlying amounts.
+        ///
+        /// Added in version 2.
+        fn get_liquidity_position(exchange_id: ExchangeId, owner: AccountId) -> Option<LiquidityPosition<AssetId, TokenBalance, Balance, ExchangeId>>;
+        /// The liquidity positions of the owner in all exchanges it provides liquidity to.
+        ///
+        /// Added in version 2.
+        fn get_liquidity_positions(owner: AccountId) -> Vec<LiquidityPosition<AssetId, TokenBalance, Balance, ExchangeId>>;
+        /// The path with the best amount for the exact input or output amount, and the amounts of every hop.
+        ///
+        /// Added in version 2.
+        fn find_best_route(
+            asset_in: SwapAsset<AssetId>,
+            asset_out: SwapAsset<AssetId>,
+            amount: AssetBalance,
+            max_hops: u32,
+            kind: SwapKind,
+        ) -> Option<SwapRoute<ExchangeId, AssetId, AssetBalance>>;
     }
 }
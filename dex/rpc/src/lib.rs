@@ -14,11 +14,15 @@ use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
 use jsonrpc_derive::rpc;
 #[cfg(feature = "std")]
 use serde::{de, Deserialize, ser, Serialize};
-use sp_api::ProvideRuntimeApi;
-use sp_blockchain::HeaderBackend;
+use serde_json::{json, Value};
+use sp_api::{ApiErrorExt, ApiExt, ProvideRuntimeApi};
+use sp_blockchain::{Error as ClientError, HeaderBackend};
 use sp_runtime::{generic::BlockId, traits::Block as BlockT};
 
-use zenlink_dex::{Exchange, ExchangeInfo, TokenInfo};
+use zenlink_dex::{
+    paginate_exchanges, AmountQuote, AssetBalance, Exchange, ExchangeFilter, ExchangeInfo, ExchangePage, LiquidityPosition, SwapAsset,
+    SwapKind, SwapRoute, TokenInfo,
+};
 use zenlink_dex_runtime_api::ZenlinkDexApi as ZenlinkDexRuntimeApi;
 
 /// A helper struct for handling u128 serialization/deserialization of RPC.
@@ -74,9 +78,9 @@ pub trait ZenlinkDexApi<
         &self,
         at: Option<BlockHash>,
         token_id: AssetId,
-    ) -> Result<Option<TokenInfo<
+    ) -> Result<TokenInfo<
         RpcU128<TokenBalance>
-    >>>;
+    >>;
 
     #[rpc(name = "zenlinkDex_getTokenBalance")]
     fn get_token_balance(
@@ -100,28 +104,27 @@ pub trait ZenlinkDexApi<
         &self,
         at: Option<BlockHash>,
         token_id: AssetId,
-    ) -> Result<Option<ExchangeInfo<
+    ) -> Result<ExchangeInfo<
         AccountId,
         AssetId,
         RpcU128<TokenBalance>,
         RpcU128<Balance>,
         ExchangeId
-    >>>;
+    >>;
 
     #[rpc(name = "zenlinkDex_getExchangeById")]
     fn get_exchange_by_id(
         &self,
         at: Option<BlockHash>,
         id: ExchangeId,
-    ) -> Result<Option<ExchangeInfo<
+    ) -> Result<ExchangeInfo<
         AccountId,
         AssetId,
         RpcU128<TokenBalance>,
         RpcU128<Balance>,
         ExchangeId
-    >>>;
+    >>;
 
-    // TODO：Pagination
     #[rpc(name = "zenlinkDex_getExchanges")]
     fn get_exchanges(
         &self,
@@ -133,9 +136,109 @@ pub trait ZenlinkDexApi<
         RpcU128<Balance>,
         ExchangeId
     >>>;
+
+    #[rpc(name = "zenlinkDex_getExchangesPaged")]
+    fn get_exchanges_paged(
+        &self,
+        at: Option<BlockHash>,
+        cursor: Option<ExchangeId>,
+        limit: u32,
+        filter: Option<ExchangeFilter<AssetId, RpcU128<Balance>>>,
+    ) -> Result<ExchangePage<
+        AccountId,
+        AssetId,
+        RpcU128<TokenBalance>,
+        RpcU128<Balance>,
+        ExchangeId
+    >>;
+
+    #[rpc(name = "zenlinkDex_getAmountOut")]
+    fn get_amount_out(
+        &self,
+        at: Option<BlockHash>,
+        asset_in: SwapAsset<AssetId>,
+        asset_out: SwapAsset<AssetId>,
+        amount_in: RpcU128<AssetBalance>,
+    ) -> Result<Option<AmountQuote<RpcU128<AssetBalance>>>>;
+
+    #[rpc(name = "zenlinkDex_getAmountIn")]
+    fn get_amount_in(
+        &self,
+        at: Option<BlockHash>,
+        asset_in: SwapAsset<AssetId>,
+        asset_out: SwapAsset<AssetId>,
+        amount_out: RpcU128<AssetBalance>,
+    ) -> Result<Option<AmountQuote<RpcU128<AssetBalance>>>>;
+
+    #[rpc(name = "zenlinkDex_getLiquidityPosition")]
+    fn get_liquidity_position(
+        &self,
+        at: Option<BlockHash>,
+        exchange_id: ExchangeId,
+        owner: AccountId,
+    ) -> Result<LiquidityPosition<
+        AssetId,
+        RpcU128<TokenBalance>,
+        RpcU128<Balance>,
+        ExchangeId
+    >>;
+
+    #[rpc(name = "zenlinkDex_getLiquidityPositions")]
+    fn get_liquidity_positions(
+        &self,
+        at: Option<BlockHash>,
+        owner: AccountId,
+    ) -> Result<Vec<LiquidityPosition<
+        AssetId,
+        RpcU128<TokenBalance>,
+        RpcU128<Balance>,
+        ExchangeId
+    >>>;
+
+    #[rpc(name = "zenlinkDex_findBestRoute")]
+    fn find_best_route(
+        &self,
+        at: Option<BlockHash>,
+        asset_in: SwapAsset<AssetId>,
+        asset_out: SwapAsset<AssetId>,
+        amount: RpcU128<AssetBalance>,
+        max_hops: u32,
+        kind: SwapKind,
+    ) -> Result<Option<SwapRoute<ExchangeId, AssetId, RpcU128<AssetBalance>>>>;
 }
 
-const RUNTIME_ERROR: i64 = 1;
+
+/// The error codes of the RPC calls, the `data` of the error holds the details as JSON.
+pub enum Error {
+    /// The runtime trapped or failed the call.
+    RuntimeError,
+    /// The block of the given hash isn't known by the node.
+    UnknownBlock,
+    /// The runtime of the block has a too old version of the api.
+    ApiVersionMismatch,
+    /// The result of the runtime call can't be decoded.
+    DecodeError,
+    /// The exchange doesn't exist.
+    ExchangeNotFound,
+    /// The token doesn't exist.
+    TokenNotFound,
+}
+
+impl From<Error> for i64 {
+    fn from(e: Error) -> i64 {
+        match e {
+            Error::RuntimeError => 1,
+            Error::UnknownBlock => 2,
+            Error::ApiVersionMismatch => 3,
+            Error::DecodeError => 4,
+            Error::ExchangeNotFound => 5,
+            Error::TokenNotFound => 6,
+        }
+    }
+}
+
+/// The version of the runtime api the calls added after the first version need.
+const API_VERSION: u32 = 2;
 
 /// A struct that implements the `ZenlinkDexApi`.
 pub struct ZenlinkDex<C, M> {
@@ -152,46 +255,62 @@ impl<C, M> ZenlinkDex<C, M> {
     }
 }
 
+impl<C, Block> ZenlinkDex<C, Block>
+    where
+        Block: BlockT,
+        C: HeaderBackend<Block>,
+{
+    /// The block of the given hash or the best block, the hash must be known by the node.
+    fn block_id(&self, at: Option<<Block as BlockT>::Hash>) -> Result<BlockId<Block>> {
+        let hash = at.unwrap_or_else(|| self.client.info().best_hash);
+        match self.client.header(BlockId::hash(hash)).map_err(client_error_into_rpc_err)? {
+            Some(_) => Ok(BlockId::hash(hash)),
+            None => Err(rpc_err(
+                Error::UnknownBlock,
+                "Unknown block",
+                json!({ "blockHash": hash }),
+            )),
+        }
+    }
+}
+
 impl<C, Block, AccountId, AssetId, TokenBalance, Balance, ExchangeId>
 ZenlinkDexApi<<Block as BlockT>::Hash, AccountId, AssetId, TokenBalance, Balance, ExchangeId>
 for ZenlinkDex<C, Block>
     where
         Block: BlockT,
         AccountId: Codec,
-        AssetId: Codec,
+        AssetId: Codec + Clone + PartialEq + Serialize,
         TokenBalance: Codec + Display + FromStr,
-        Balance: Codec + Display + FromStr,
-        ExchangeId: Codec,
+        Balance: Codec + Display + FromStr + PartialOrd,
+        ExchangeId: Codec + PartialOrd + Copy + Serialize,
         C: Send + Sync + 'static,
         C: ProvideRuntimeApi<Block>,
         C: HeaderBackend<Block>,
         C::Api: ZenlinkDexRuntimeApi<Block, AccountId, AssetId, TokenBalance, Balance, ExchangeId>,
+        C::Api: ApiErrorExt<Error = ClientError>,
 {
     fn get_token_info(
         &self,
         at: Option<<Block as BlockT>::Hash>,
         token_id: AssetId,
-    ) -> Result<Option<TokenInfo<
+    ) -> Result<TokenInfo<
         RpcU128<TokenBalance>
-    >>> {
+    >> {
         let api = self.client.runtime_api();
-        let at = BlockId::hash(at.unwrap_or_else(
-            || self.client.info().best_hash
-        ));
+        let at = self.block_id(at)?;
 
-        Ok(api.get_token_info(&at, token_id)
-            .map(|option| {
-                option
-                    .map(|token_info| {
-                        TokenInfo {
-                            current_supply: token_info.current_supply.into(),
-                            name: token_info.name,
-                            symbol: token_info.symbol,
-                            decimals: token_info.decimals,
-                        }
-                    })
+        api.get_token_info(&at, token_id.clone())
+            .map_err(client_error_into_rpc_err)?
+            .map(|token_info| {
+                TokenInfo {
+                    current_supply: token_info.current_supply.into(),
+                    name: token_info.name,
+                    symbol: token_info.symbol,
+                    decimals: token_info.decimals,
+                }
             })
-            .map_err(runtime_error_into_rpc_err)?)
+            .ok_or_else(|| token_not_found(&token_id))
     }
 
     fn get_token_balance(
@@ -201,13 +320,11 @@ for ZenlinkDex<C, Block>
         owner: AccountId,
     ) -> Result<RpcU128<TokenBalance>> {
         let api = self.client.runtime_api();
-        let at = BlockId::hash(at.unwrap_or_else(
-            || self.client.info().best_hash
-        ));
+        let at = self.block_id(at)?;
 
         Ok(api.get_token_balance(&at, token_id, owner)
             .map(|token_balance| token_balance.into())
-            .map_err(runtime_error_into_rpc_err)?)
+            .map_err(client_error_into_rpc_err)?)
     }
 
     fn get_token_allowance(
@@ -218,84 +335,83 @@ for ZenlinkDex<C, Block>
         spender: AccountId,
     ) -> Result<RpcU128<TokenBalance>> {
         let api = self.client.runtime_api();
-        let at = BlockId::hash(at.unwrap_or_else(
-            || self.client.info().best_hash)
-        );
+        let at = self.block_id(at)?;
 
         Ok(api.get_token_allowance(&at, token_id, owner, spender)
             .map(|token_balance| token_balance.into())
-            .map_err(runtime_error_into_rpc_err)?)
+            .map_err(client_error_into_rpc_err)?)
     }
 
     fn get_exchange_by_token_id(
         &self,
         at: Option<<Block as BlockT>::Hash>,
         token_id: AssetId,
-    ) -> Result<Option<ExchangeInfo<
+    ) -> Result<ExchangeInfo<
         AccountId,
         AssetId,
         RpcU128<TokenBalance>,
         RpcU128<Balance>,
         ExchangeId
-    >>> {
+    >> {
         let api = self.client.runtime_api();
-        let at = BlockId::hash(at.unwrap_or_else(
-            || self.client.info().best_hash)
-        );
+        let at = self.block_id(at)?;
 
-        Ok(api.get_exchange_by_token_id(&at, token_id)
-            .map(|option| {
-                option
-                    .map(|exchange_info| {
-                        ExchangeInfo {
-                            exchange: Exchange {
-                                token_id: exchange_info.exchange.token_id,
-                                liquidity_id: exchange_info.exchange.liquidity_id,
-                                account: exchange_info.exchange.account,
-                            },
-                            token_reserve: exchange_info.token_reserve.into(),
-                            currency_reserve: exchange_info.currency_reserve.into(),
-                            exchange_id: exchange_info.exchange_id,
-                        }
-                    })
-            }).map_err(runtime_error_into_rpc_err)?)
+        // The runtimes before version 2 of the api return the exchange of version 1.
+        let has_api_version_2 = api
+            .has_api_with::<dyn ZenlinkDexRuntimeApi<Block, AccountId, AssetId, TokenBalance, Balance, ExchangeId>, _>(
+                &at,
+                |version| version >= API_VERSION,
+            )
+            .map_err(client_error_into_rpc_err)?;
+        let exchange_info = if has_api_version_2 {
+            api.get_exchange_by_token_id(&at, token_id.clone())
+        } else {
+            #[allow(deprecated)]
+            let exchange_info = api.get_exchange_by_token_id_before_version_2(&at, token_id.clone());
+            exchange_info.map(|exchange_info| exchange_info.map(Into::into))
+        };
+
+        exchange_info
+            .map_err(client_error_into_rpc_err)?
+            .map(exchange_info_into_rpc)
+            .ok_or_else(|| exchange_not_found(json!({ "tokenId": token_id })))
     }
 
     fn get_exchange_by_id(
         &self,
         at: Option<<Block as BlockT>::Hash>,
         id: ExchangeId,
-    ) -> Result<Option<ExchangeInfo<
+    ) -> Result<ExchangeInfo<
         AccountId,
         AssetId,
         RpcU128<TokenBalance>,
         RpcU128<Balance>,
         ExchangeId
-    >>> {
+    >> {
         let api = self.client.runtime_api();
-        let at = BlockId::hash(at.unwrap_or_else(
-            || self.client.info().best_hash)
-        );
+        let at = self.block_id(at)?;
 
-        Ok(api.get_exchange_by_id(&at, id)
-            .map(|option| {
-                option
-                    .map(|exchange_info| {
-                        ExchangeInfo {
-                            exchange: Exchange {
-                                token_id: exchange_info.exchange.token_id,
-                                liquidity_id: exchange_info.exchange.liquidity_id,
-                                account: exchange_info.exchange.account,
-                            },
-                            token_reserve: exchange_info.token_reserve.into(),
-                            currency_reserve: exchange_info.currency_reserve.into(),
-                            exchange_id: exchange_info.exchange_id,
-                        }
-                    })
-            }).map_err(runtime_error_into_rpc_err)?)
+        // The runtimes before version 2 of the api return the exchange of version 1.
+        let has_api_version_2 = api
+            .has_api_with::<dyn ZenlinkDexRuntimeApi<Block, AccountId, AssetId, TokenBalance, Balance, ExchangeId>, _>(
+                &at,
+                |version| version >= API_VERSION,
+            )
+            .map_err(client_error_into_rpc_err)?;
+        let exchange_info = if has_api_version_2 {
+            api.get_exchange_by_id(&at, id)
+        } else {
+            #[allow(deprecated)]
+            let exchange_info = api.get_exchange_by_id_before_version_2(&at, id);
+            exchange_info.map(|exchange_info| exchange_info.map(Into::into))
+        };
+
+        exchange_info
+            .map_err(client_error_into_rpc_err)?
+            .map(exchange_info_into_rpc)
+            .ok_or_else(|| exchange_not_found(json!({ "exchangeId": id })))
     }
 
-    // TODO：Pagination
     fn get_exchanges(
         &self,
         at: Option<<Block as BlockT>::Hash>,
@@ -307,33 +423,368 @@ for ZenlinkDex<C, Block>
         ExchangeId
     >>> {
         let api = self.client.runtime_api();
-        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+        let at = self.block_id(at)?;
 
-        Ok(api.get_exchanges(&at)
+        // The runtimes before version 2 of the api return the exchanges of version 1.
+        let has_api_version_2 = api
+            .has_api_with::<dyn ZenlinkDexRuntimeApi<Block, AccountId, AssetId, TokenBalance, Balance, ExchangeId>, _>(
+                &at,
+                |version| version >= API_VERSION,
+            )
+            .map_err(client_error_into_rpc_err)?;
+        let exchanges = if has_api_version_2 {
+            api.get_exchanges(&at)
+        } else {
+            #[allow(deprecated)]
+            let exchanges = api.get_exchanges_before_version_2(&at);
+            exchanges.map(|exchanges| exchanges.into_iter().map(Into::into).collect::<Vec<_>>())
+        };
+
+        Ok(exchanges
             .map(|exchanges| {
                 exchanges
                     .into_iter()
-                    .map(|exchange_info| ExchangeInfo {
-                        exchange: Exchange {
-                            token_id: exchange_info.exchange.token_id,
-                            liquidity_id: exchange_info.exchange.liquidity_id,
-                            account: exchange_info.exchange.account,
-                        },
-                        token_reserve: exchange_info.token_reserve.into(),
-                        currency_reserve: exchange_info.currency_reserve.into(),
-                        exchange_id: exchange_info.exchange_id,
-                    })
+                    .map(exchange_info_into_rpc)
+                    .collect::<Vec<_>>()
+            })
+            .map_err(client_error_into_rpc_err)?)
+    }
+
+    fn get_exchanges_paged(
+        &self,
+        at: Option<<Block as BlockT>::Hash>,
+        cursor: Option<ExchangeId>,
+        limit: u32,
+        filter: Option<ExchangeFilter<AssetId, RpcU128<Balance>>>,
+    ) -> Result<ExchangePage<
+        AccountId,
+        AssetId,
+        RpcU128<TokenBalance>,
+        RpcU128<Balance>,
+        ExchangeId
+    >> {
+        let api = self.client.runtime_api();
+        let at = self.block_id(at)?;
+
+        let filter = filter
+            .map(|filter| ExchangeFilter {
+                token_id: filter.token_id,
+                min_currency_reserve: filter.min_currency_reserve.map(|reserve| reserve.0),
+            })
+            .unwrap_or_default();
+
+        // The runtimes before version 2 of the api are paginated here.
+        let paged = api
+            .has_api_with::<dyn ZenlinkDexRuntimeApi<Block, AccountId, AssetId, TokenBalance, Balance, ExchangeId>, _>(
+                &at,
+                |version| version >= API_VERSION,
+            )
+            .map_err(client_error_into_rpc_err)?;
+        let page = if paged {
+            api.get_exchanges_paged(&at, cursor, limit, filter)
+        } else {
+            #[allow(deprecated)]
+            let exchanges = api.get_exchanges_before_version_2(&at);
+            exchanges.map(|exchanges| {
+                let exchanges = exchanges.into_iter().map(Into::into).collect::<Vec<_>>();
+                paginate_exchanges(exchanges, cursor, limit, &filter)
+            })
+        }.map_err(client_error_into_rpc_err)?;
+
+        Ok(ExchangePage {
+            exchanges: page.exchanges.into_iter().map(exchange_info_into_rpc).collect(),
+            next_cursor: page.next_cursor,
+            total: page.total,
+        })
+    }
+
+    fn get_amount_out(
+        &self,
+        at: Option<<Block as BlockT>::Hash>,
+        asset_in: SwapAsset<AssetId>,
+        asset_out: SwapAsset<AssetId>,
+        amount_in: RpcU128<AssetBalance>,
+    ) -> Result<Option<AmountQuote<RpcU128<AssetBalance>>>> {
+        let api = self.client.runtime_api();
+        let at = self.block_id(at)?;
+
+        let quote_api = api
+            .has_api_with::<dyn ZenlinkDexRuntimeApi<Block, AccountId, AssetId, TokenBalance, Balance, ExchangeId>, _>(
+                &at,
+                |version| version >= API_VERSION,
+            )
+            .map_err(client_error_into_rpc_err)?;
+        if !quote_api {
+            return Err(api_version_mismatch("zenlinkDex_getAmountOut"));
+        }
+
+        match api.get_amount_out(&at, asset_in.clone(), asset_out.clone(), amount_in.0)
+            .map_err(client_error_into_rpc_err)? {
+            Some(quote) => Ok(Some(quote_into_rpc(quote))),
+            // Tell the missing exchange apart from the lack of liquidity, the pair of the tokens quoted nothing
+            None => {
+                for asset in [asset_in, asset_out].iter() {
+                    if let SwapAsset::Token(token_id) = asset {
+                        let exchange = api.get_exchange_by_token_id(&at, token_id.clone())
+                            .map_err(client_error_into_rpc_err)?;
+                        if exchange.is_none() {
+                            return Err(exchange_not_found(json!({ "tokenId": token_id })));
+                        }
+                    }
+                }
+                Ok(None)
+            }
+        }
+    }
+
+    fn get_amount_in(
+        &self,
+        at: Option<<Block as BlockT>::Hash>,
+        asset_in: SwapAsset<AssetId>,
+        asset_out: SwapAsset<AssetId>,
+        amount_out: RpcU128<AssetBalance>,
+    ) -> Result<Option<AmountQuote<RpcU128<AssetBalance>>>> {
+        let api = self.client.runtime_api();
+        let at = self.block_id(at)?;
+
+        let quote_api = api
+            .has_api_with::<dyn ZenlinkDexRuntimeApi<Block, AccountId, AssetId, TokenBalance, Balance, ExchangeId>, _>(
+                &at,
+                |version| version >= API_VERSION,
+            )
+            .map_err(client_error_into_rpc_err)?;
+        if !quote_api {
+            return Err(api_version_mismatch("zenlinkDex_getAmountIn"));
+        }
+
+        match api.get_amount_in(&at, asset_in.clone(), asset_out.clone(), amount_out.0)
+            .map_err(client_error_into_rpc_err)? {
+            Some(quote) => Ok(Some(quote_into_rpc(quote))),
+            // Tell the missing exchange apart from the lack of liquidity, the pair of the tokens quoted nothing
+            None => {
+                for asset in [asset_in, asset_out].iter() {
+                    if let SwapAsset::Token(token_id) = asset {
+                        let exchange = api.get_exchange_by_token_id(&at, token_id.clone())
+                            .map_err(client_error_into_rpc_err)?;
+                        if exchange.is_none() {
+                            return Err(exchange_not_found(json!({ "tokenId": token_id })));
+                        }
+                    }
+                }
+                Ok(None)
+            }
+        }
+    }
+
+    fn get_liquidity_position(
+        &self,
+        at: Option<<Block as BlockT>::Hash>,
+        exchange_id: ExchangeId,
+        owner: AccountId,
+    ) -> Result<LiquidityPosition<
+        AssetId,
+        RpcU128<TokenBalance>,
+        RpcU128<Balance>,
+        ExchangeId
+    >> {
+        let api = self.client.runtime_api();
+        let at = self.block_id(at)?;
+
+        let position_api = api
+            .has_api_with::<dyn ZenlinkDexRuntimeApi<Block, AccountId, AssetId, TokenBalance, Balance, ExchangeId>, _>(
+                &at,
+                |version| version >= API_VERSION,
+            )
+            .map_err(client_error_into_rpc_err)?;
+        if !position_api {
+            return Err(api_version_mismatch("zenlinkDex_getLiquidityPosition"));
+        }
+
+        api.get_liquidity_position(&at, exchange_id, owner)
+            .map_err(client_error_into_rpc_err)?
+            .map(liquidity_position_into_rpc)
+            .ok_or_else(|| exchange_not_found(json!({ "exchangeId": exchange_id })))
+    }
+
+    fn get_liquidity_positions(
+        &self,
+        at: Option<<Block as BlockT>::Hash>,
+        owner: AccountId,
+    ) -> Result<Vec<LiquidityPosition<
+        AssetId,
+        RpcU128<TokenBalance>,
+        RpcU128<Balance>,
+        ExchangeId
+    >>> {
+        let api = self.client.runtime_api();
+        let at = self.block_id(at)?;
+
+        let position_api = api
+            .has_api_with::<dyn ZenlinkDexRuntimeApi<Block, AccountId, AssetId, TokenBalance, Balance, ExchangeId>, _>(
+                &at,
+                |version| version >= API_VERSION,
+            )
+            .map_err(client_error_into_rpc_err)?;
+        if !position_api {
+            return Err(api_version_mismatch("zenlinkDex_getLiquidityPositions"));
+        }
+
+        Ok(api.get_liquidity_positions(&at, owner)
+            .map(|positions| {
+                positions
+                    .into_iter()
+                    .map(liquidity_position_into_rpc)
                     .collect::<Vec<_>>()
             })
-            .map_err(runtime_error_into_rpc_err)?)
+            .map_err(client_error_into_rpc_err)?)
+    }
+
+    fn find_best_route(
+        &self,
+        at: Option<<Block as BlockT>::Hash>,
+        asset_in: SwapAsset<AssetId>,
+        asset_out: SwapAsset<AssetId>,
+        amount: RpcU128<AssetBalance>,
+        max_hops: u32,
+        kind: SwapKind,
+    ) -> Result<Option<SwapRoute<ExchangeId, AssetId, RpcU128<AssetBalance>>>> {
+        let api = self.client.runtime_api();
+        let at = self.block_id(at)?;
+
+        let route_api = api
+            .has_api_with::<dyn ZenlinkDexRuntimeApi<Block, AccountId, AssetId, TokenBalance, Balance, ExchangeId>, _>(
+                &at,
+                |version| version >= API_VERSION,
+            )
+            .map_err(client_error_into_rpc_err)?;
+        if !route_api {
+            return Err(api_version_mismatch("zenlinkDex_findBestRoute"));
+        }
+
+        Ok(api.find_best_route(&at, asset_in, asset_out, amount.0, max_hops, kind)
+            .map(|option| {
+                option.map(|route| SwapRoute {
+                    path: route.path,
+                    amounts: route.amounts.into_iter().map(Into::into).collect(),
+                })
+            })
+            .map_err(client_error_into_rpc_err)?)
+    }
+}
+
+/// Converts the reserves of an exchange into the number strings.
+fn exchange_info_into_rpc<AccountId, AssetId, TokenBalance, Balance, ExchangeId>(
+    exchange_info: ExchangeInfo<AccountId, AssetId, TokenBalance, Balance, ExchangeId>,
+) -> ExchangeInfo<AccountId, AssetId, RpcU128<TokenBalance>, RpcU128<Balance>, ExchangeId>
+    where
+        TokenBalance: Display + FromStr,
+        Balance: Display + FromStr,
+{
+    ExchangeInfo {
+        exchange: Exchange {
+            token_id: exchange_info.exchange.token_id,
+            liquidity_id: exchange_info.exchange.liquidity_id,
+            account: exchange_info.exchange.account,
+            fee_rate: exchange_info.exchange.fee_rate,
+            token_reserve: exchange_info.exchange.token_reserve.into(),
+            currency_reserve: exchange_info.exchange.currency_reserve.into(),
+        },
+        exchange_id: exchange_info.exchange_id,
+    }
+}
+
+/// Converts the amounts of a liquidity position into the number strings.
+fn liquidity_position_into_rpc<AssetId, TokenBalance, Balance, ExchangeId>(
+    position: LiquidityPosition<AssetId, TokenBalance, Balance, ExchangeId>,
+) -> LiquidityPosition<AssetId, RpcU128<TokenBalance>, RpcU128<Balance>, ExchangeId>
+    where
+        TokenBalance: Display + FromStr,
+        Balance: Display + FromStr,
+{
+    LiquidityPosition {
+        exchange_id: position.exchange_id,
+        liquidity_id: position.liquidity_id,
+        liquidity: position.liquidity.into(),
+        total_liquidity: position.total_liquidity.into(),
+        share: position.share,
+        currency_amount: position.currency_amount.into(),
+        token_amount: position.token_amount.into(),
+    }
+}
+
+/// Converts the amount of a quote into the number string.
+fn quote_into_rpc<TokenBalance: Display + FromStr>(quote: AmountQuote<TokenBalance>) -> AmountQuote<RpcU128<TokenBalance>> {
+    AmountQuote {
+        amount: quote.amount.into(),
+        price_impact: quote.price_impact,
     }
 }
 
-/// Converts a runtime trap into an RPC error.
-fn runtime_error_into_rpc_err(err: impl std::fmt::Debug) -> RpcError {
+fn rpc_err(error: Error, message: &str, data: Value) -> RpcError {
     RpcError {
-        code: ErrorCode::ServerError(RUNTIME_ERROR),
-        message: "Runtime trapped".into(),
-        data: Some(format!("{:?}", err).into()),
+        code: ErrorCode::ServerError(error.into()),
+        message: message.into(),
+        data: Some(data),
     }
-}
\ No newline at end of file
+}
+
+fn token_not_found(token_id: &impl Serialize) -> RpcError {
+    rpc_err(Error::TokenNotFound, "Token not found", json!({ "tokenId": token_id }))
+}
+
+fn exchange_not_found(data: Value) -> RpcError {
+    rpc_err(Error::ExchangeNotFound, "Exchange not found", data)
+}
+
+fn api_version_mismatch(method: &str) -> RpcError {
+    rpc_err(
+        Error::ApiVersionMismatch,
+        "Runtime api version mismatch",
+        json!({ "method": method, "requiredVersion": API_VERSION }),
+    )
+}
+
+/// Converts a failure of the client or the runtime into an RPC error.
+fn client_error_into_rpc_err(err: ClientError) -> RpcError {
+    match err {
+        ClientError::UnknownBlock(block) => rpc_err(
+            Error::UnknownBlock,
+            "Unknown block",
+            json!({ "block": block }),
+        ),
+        ClientError::CallResultDecode(method, err) => rpc_err(
+            Error::DecodeError,
+            "Decoding the runtime result failed",
+            json!({ "method": method, "error": err.to_string() }),
+        ),
+        err => rpc_err(
+            Error::RuntimeError,
+            "Runtime trapped",
+            json!({ "error": format!("{:?}", err) }),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn client_error_into_rpc_err_should_work() {
+        let err = client_error_into_rpc_err(ClientError::UnknownBlock("0x01".into()));
+        assert_eq!(err.code, ErrorCode::ServerError(2));
+        assert_eq!(err.message, "Unknown block");
+        assert_eq!(err.data, Some(json!({ "block": "0x01" })));
+
+        let decode_err = || codec::Error::from("Not enough data to fill buffer");
+        let err = client_error_into_rpc_err(ClientError::CallResultDecode("get_amount_out", decode_err()));
+        assert_eq!(err.code, ErrorCode::ServerError(4));
+        assert_eq!(err.message, "Decoding the runtime result failed");
+        assert_eq!(err.data, Some(json!({ "method": "get_amount_out", "error": decode_err().to_string() })));
+
+        let err = client_error_into_rpc_err(ClientError::Msg("wasm trap".into()));
+        assert_eq!(err.code, ErrorCode::ServerError(1));
+        assert_eq!(err.message, "Runtime trapped");
+        assert_eq!(err.data, Some(json!({ "error": format!("{:?}", ClientError::Msg("wasm trap".into())) })));
+    }
+}
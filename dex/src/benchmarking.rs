@@ -0,0 +1,639 @@
+//! Benchmarks for the dex module.
+
+#![cfg(feature = "runtime-benchmarks")]
+
+use super::*;
+use frame_benchmarking::{account, benchmarks, whitelisted_caller};
+use frame_system::RawOrigin;
+use sp_runtime::traits::Bounded;
+use sp_std::vec;
+
+const SEED: u32 = 0;
+
+const BENCH_TOKEN: &AssetInfo = &AssetInfo {
+    name: *b"zenlinkbenchmark",
+    symbol: *b"BENCH___",
+    decimals: 0u8,
+};
+
+const TOKEN_SUPPLY: u32 = 4_000_000_000;
+const TOKEN_LIQUIDITY: u32 = 1_000_000_000;
+const TOKEN_AMOUNT: u32 = 1_000_000;
+// In units of the existential deposit, at the price of 1000 tokens.
+const CURRENCY_LIQUIDITY: u32 = 1_000_000;
+const CURRENCY_AMOUNT: u32 = 1_000;
+
+/// The amount of currency in units of the existential deposit.
+fn currency<T: Trait>(amount: u32) -> BalanceOf<T> {
+    T::Currency::minimum_balance().max(One::one()).saturating_mul(amount.into())
+}
+
+fn deadline<T: Trait>() -> T::BlockNumber {
+    T::BlockNumber::max_value()
+}
+
+/// Move to the next block, so the calls accumulate the prices.
+fn next_block<T: Trait>() {
+    let now = frame_system::Module::<T>::block_number();
+    frame_system::Module::<T>::set_block_number(now + One::one());
+}
+
+fn funded_caller<T: Trait>() -> T::AccountId {
+    let caller: T::AccountId = whitelisted_caller();
+    T::Currency::make_free_balance_be(&caller, currency::<T>(TOKEN_LIQUIDITY));
+    caller
+}
+
+fn issue_token<T: Trait>(caller: &T::AccountId) -> T::AssetId {
+    <zenlink_assets::Module<T>>::inner_issue(caller, TOKEN_SUPPLY.into(), BENCH_TOKEN)
+}
+
+/// Turn the protocol fee on, so the liquidity calls mint it.
+fn set_fee_to<T: Trait>() {
+    <FeeTo<T>>::put(account::<T::AccountId>("fee_to", 0, SEED));
+}
+
+/// Keep the most price observations of the exchange or pair, so the next reserves change drops
+/// the oldest one and stores the longest list.
+fn fill_price_observations<T: Trait>(exchange_id: T::ExchangeId) {
+    let observation = Module::<T>::price_cumulative(exchange_id);
+    let observations = vec![observation; Module::<T>::max_price_observations() as usize];
+    <PriceObservations<T>>::insert(exchange_id, observations);
+}
+
+/// Create the exchange of a new token with liquidity, then grow its k by a swap.
+fn setup_exchange<T: Trait>(caller: &T::AccountId) -> Result<(T::AssetId, T::ExchangeId), &'static str> {
+    let token_id = issue_token::<T>(caller);
+    let exchange_id = Module::<T>::next_exchange_id();
+    Module::<T>::create_exchange(RawOrigin::Signed(caller.clone()).into(), token_id)?;
+
+    set_fee_to::<T>();
+    Module::<T>::add_liquidity(
+        RawOrigin::Signed(caller.clone()).into(),
+        SwapHandler::ExchangeId(exchange_id),
+        currency::<T>(CURRENCY_LIQUIDITY),
+        Zero::zero(),
+        TOKEN_LIQUIDITY.into(),
+        deadline::<T>(),
+    )?;
+    Module::<T>::currency_to_token_input(
+        RawOrigin::Signed(caller.clone()).into(),
+        SwapHandler::ExchangeId(exchange_id),
+        currency::<T>(CURRENCY_AMOUNT),
+        One::one(),
+        deadline::<T>(),
+        caller.clone(),
+    )?;
+    fill_price_observations::<T>(exchange_id);
+
+    Ok((token_id, exchange_id))
+}
+
+/// Create the pair of the tokens with liquidity, then grow its k by a swap.
+fn setup_pair<T: Trait>(
+    caller: &T::AccountId,
+    token_a: T::AssetId,
+    token_b: T::AssetId,
+) -> Result<T::ExchangeId, &'static str> {
+    let pair_id = Module::<T>::next_exchange_id();
+    Module::<T>::create_pair(RawOrigin::Signed(caller.clone()).into(), token_a, token_b)?;
+
+    set_fee_to::<T>();
+    Module::<T>::add_pair_liquidity(
+        RawOrigin::Signed(caller.clone()).into(),
+        token_a,
+        token_b,
+        TOKEN_LIQUIDITY.into(),
+        Zero::zero(),
+        TOKEN_LIQUIDITY.into(),
+        deadline::<T>(),
+    )?;
+    Module::<T>::token_to_token_pair_input(
+        RawOrigin::Signed(caller.clone()).into(),
+        token_a,
+        token_b,
+        TOKEN_AMOUNT.into(),
+        One::one(),
+        deadline::<T>(),
+        caller.clone(),
+    )?;
+    fill_price_observations::<T>(pair_id);
+
+    Ok(pair_id)
+}
+
+/// Create `hops` pairs connecting `hops + 1` new tokens one by one.
+/// Return the first token and the path.
+fn setup_path<T: Trait>(
+    caller: &T::AccountId,
+    hops: u32,
+) -> Result<(T::AssetId, Vec<SwapHandlerOf<T>>), &'static str> {
+    let first_token = issue_token::<T>(caller);
+    let mut token_in = first_token;
+    let mut path = Vec::new();
+    for _ in 0..hops {
+        let token_out = issue_token::<T>(caller);
+        path.push(SwapHandler::ExchangeId(setup_pair::<T>(caller, token_in, token_out)?));
+        token_in = token_out;
+    }
+
+    Ok((first_token, path))
+}
+
+benchmarks! {
+    _ { }
+
+    create_exchange {
+        let caller = funded_caller::<T>();
+        let token_id = issue_token::<T>(&caller);
+    }: _(RawOrigin::Signed(caller), token_id)
+    verify {
+        assert!(Module::<T>::token_to_exchange(token_id).is_some());
+    }
+
+    set_exchange_fee_rate {
+        let caller = funded_caller::<T>();
+        let (_, exchange_id) = setup_exchange::<T>(&caller)?;
+        let origin = T::ControlOrigin::successful_origin();
+    }: {
+        Module::<T>::set_exchange_fee_rate(origin, SwapHandler::ExchangeId(exchange_id), Some(Permill::from_percent(1)))?;
+    }
+    verify {
+        assert_eq!(Module::<T>::get_exchange(exchange_id).unwrap().fee_rate, Some(Permill::from_percent(1)));
+    }
+
+    set_fee_to {
+        let origin = T::ControlOrigin::successful_origin();
+        let fee_to: T::AccountId = account("fee_to", 0, SEED);
+    }: {
+        Module::<T>::set_fee_to(origin, Some(fee_to.clone()))?;
+    }
+    verify {
+        assert_eq!(Module::<T>::fee_to(), Some(fee_to));
+    }
+
+    set_exchange_status {
+        let caller = funded_caller::<T>();
+        let (_, exchange_id) = setup_exchange::<T>(&caller)?;
+        let origin = T::ControlOrigin::successful_origin();
+    }: {
+        Module::<T>::set_exchange_status(origin, SwapHandler::ExchangeId(exchange_id), ExchangeStatus::SwapsPaused)?;
+    }
+    verify {
+        assert_eq!(Module::<T>::exchange_status(exchange_id), ExchangeStatus::SwapsPaused);
+    }
+
+    set_global_status {
+        let origin = T::ControlOrigin::successful_origin();
+    }: {
+        Module::<T>::set_global_status(origin, ExchangeStatus::SwapsPaused)?;
+    }
+    verify {
+        assert_eq!(Module::<T>::global_status(), ExchangeStatus::SwapsPaused);
+    }
+
+    add_liquidity {
+        let caller = funded_caller::<T>();
+        let (_, exchange_id) = setup_exchange::<T>(&caller)?;
+        next_block::<T>();
+    }: _(
+        RawOrigin::Signed(caller),
+        SwapHandler::ExchangeId(exchange_id),
+        currency::<T>(CURRENCY_AMOUNT),
+        One::one(),
+        TOKEN_LIQUIDITY.into(),
+        deadline::<T>()
+    )
+    verify {
+        let exchange = Module::<T>::get_exchange(exchange_id).unwrap();
+        assert_eq!(exchange.currency_reserve, currency::<T>(CURRENCY_LIQUIDITY + 2 * CURRENCY_AMOUNT));
+    }
+
+    add_liquidity_by_amounts {
+        let caller = funded_caller::<T>();
+        let (_, exchange_id) = setup_exchange::<T>(&caller)?;
+        next_block::<T>();
+    }: _(
+        RawOrigin::Signed(caller),
+        SwapHandler::ExchangeId(exchange_id),
+        currency::<T>(CURRENCY_AMOUNT),
+        TOKEN_LIQUIDITY.into(),
+        One::one(),
+        One::one(),
+        deadline::<T>()
+    )
+    verify {
+        let exchange = Module::<T>::get_exchange(exchange_id).unwrap();
+        assert_eq!(exchange.currency_reserve, currency::<T>(CURRENCY_LIQUIDITY + 2 * CURRENCY_AMOUNT));
+    }
+
+    remove_liquidity {
+        let caller = funded_caller::<T>();
+        let (_, exchange_id) = setup_exchange::<T>(&caller)?;
+        let liquidity_id = Module::<T>::get_exchange(exchange_id).unwrap().liquidity_id;
+        let zlk_to_burn = <zenlink_assets::Module<T>>::balance_of(&liquidity_id, &caller) / 1000u32.into();
+        next_block::<T>();
+    }: _(
+        RawOrigin::Signed(caller),
+        SwapHandler::ExchangeId(exchange_id),
+        zlk_to_burn,
+        Zero::zero(),
+        Zero::zero(),
+        deadline::<T>()
+    )
+    verify {
+        let exchange = Module::<T>::get_exchange(exchange_id).unwrap();
+        assert!(exchange.currency_reserve < currency::<T>(CURRENCY_LIQUIDITY + CURRENCY_AMOUNT));
+    }
+
+    add_liquidity_single_sided {
+        let caller = funded_caller::<T>();
+        let (token_id, exchange_id) = setup_exchange::<T>(&caller)?;
+        let liquidity_id = Module::<T>::get_exchange(exchange_id).unwrap().liquidity_id;
+        let liquidity = <zenlink_assets::Module<T>>::balance_of(&liquidity_id, &caller);
+        next_block::<T>();
+    }: _(
+        RawOrigin::Signed(caller.clone()),
+        SwapHandler::ExchangeId(exchange_id),
+        SwapAsset::Token(token_id),
+        TOKEN_AMOUNT.into(),
+        One::one(),
+        deadline::<T>()
+    )
+    verify {
+        assert!(<zenlink_assets::Module<T>>::balance_of(&liquidity_id, &caller) > liquidity);
+    }
+
+    remove_liquidity_single_sided {
+        let caller = funded_caller::<T>();
+        let (token_id, exchange_id) = setup_exchange::<T>(&caller)?;
+        let liquidity_id = Module::<T>::get_exchange(exchange_id).unwrap().liquidity_id;
+        let zlk_to_burn = <zenlink_assets::Module<T>>::balance_of(&liquidity_id, &caller) / 1000u32.into();
+        let token_balance = <zenlink_assets::Module<T>>::balance_of(&token_id, &caller);
+        next_block::<T>();
+    }: _(
+        RawOrigin::Signed(caller.clone()),
+        SwapHandler::ExchangeId(exchange_id),
+        zlk_to_burn,
+        SwapAsset::Token(token_id),
+        One::one(),
+        deadline::<T>()
+    )
+    verify {
+        assert!(<zenlink_assets::Module<T>>::balance_of(&token_id, &caller) > token_balance);
+    }
+
+    currency_to_token_input {
+        let caller = funded_caller::<T>();
+        let (token_id, exchange_id) = setup_exchange::<T>(&caller)?;
+        let token_balance = <zenlink_assets::Module<T>>::balance_of(&token_id, &caller);
+        next_block::<T>();
+    }: _(
+        RawOrigin::Signed(caller.clone()),
+        SwapHandler::ExchangeId(exchange_id),
+        currency::<T>(CURRENCY_AMOUNT),
+        One::one(),
+        deadline::<T>(),
+        caller.clone()
+    )
+    verify {
+        assert!(<zenlink_assets::Module<T>>::balance_of(&token_id, &caller) > token_balance);
+    }
+
+    currency_to_token_output {
+        let caller = funded_caller::<T>();
+        let (token_id, exchange_id) = setup_exchange::<T>(&caller)?;
+        let token_balance = <zenlink_assets::Module<T>>::balance_of(&token_id, &caller);
+        next_block::<T>();
+    }: _(
+        RawOrigin::Signed(caller.clone()),
+        SwapHandler::ExchangeId(exchange_id),
+        TOKEN_AMOUNT.into(),
+        currency::<T>(CURRENCY_LIQUIDITY),
+        deadline::<T>(),
+        caller.clone()
+    )
+    verify {
+        assert_eq!(
+            <zenlink_assets::Module<T>>::balance_of(&token_id, &caller),
+            token_balance + TOKEN_AMOUNT.into()
+        );
+    }
+
+    token_to_currency_input {
+        let caller = funded_caller::<T>();
+        let (_, exchange_id) = setup_exchange::<T>(&caller)?;
+        let currency_balance = T::Currency::free_balance(&caller);
+        next_block::<T>();
+    }: _(
+        RawOrigin::Signed(caller.clone()),
+        SwapHandler::ExchangeId(exchange_id),
+        TOKEN_AMOUNT.into(),
+        One::one(),
+        deadline::<T>(),
+        caller.clone()
+    )
+    verify {
+        assert!(T::Currency::free_balance(&caller) > currency_balance);
+    }
+
+    token_to_currency_output {
+        let caller = funded_caller::<T>();
+        let (_, exchange_id) = setup_exchange::<T>(&caller)?;
+        let currency_balance = T::Currency::free_balance(&caller);
+        next_block::<T>();
+    }: _(
+        RawOrigin::Signed(caller.clone()),
+        SwapHandler::ExchangeId(exchange_id),
+        currency::<T>(CURRENCY_AMOUNT),
+        TOKEN_LIQUIDITY.into(),
+        deadline::<T>(),
+        caller.clone()
+    )
+    verify {
+        assert_eq!(T::Currency::free_balance(&caller), currency_balance + currency::<T>(CURRENCY_AMOUNT));
+    }
+
+    token_to_token_input {
+        let caller = funded_caller::<T>();
+        let (_, exchange_id) = setup_exchange::<T>(&caller)?;
+        let (other_token_id, other_exchange_id) = setup_exchange::<T>(&caller)?;
+        let other_token_balance = <zenlink_assets::Module<T>>::balance_of(&other_token_id, &caller);
+        next_block::<T>();
+    }: _(
+        RawOrigin::Signed(caller.clone()),
+        SwapHandler::ExchangeId(exchange_id),
+        SwapHandler::ExchangeId(other_exchange_id),
+        TOKEN_AMOUNT.into(),
+        One::one(),
+        deadline::<T>(),
+        caller.clone()
+    )
+    verify {
+        assert!(<zenlink_assets::Module<T>>::balance_of(&other_token_id, &caller) > other_token_balance);
+    }
+
+    token_to_token_output {
+        let caller = funded_caller::<T>();
+        let (_, exchange_id) = setup_exchange::<T>(&caller)?;
+        let (other_token_id, other_exchange_id) = setup_exchange::<T>(&caller)?;
+        let other_token_balance = <zenlink_assets::Module<T>>::balance_of(&other_token_id, &caller);
+        next_block::<T>();
+    }: _(
+        RawOrigin::Signed(caller.clone()),
+        SwapHandler::ExchangeId(exchange_id),
+        SwapHandler::ExchangeId(other_exchange_id),
+        TOKEN_AMOUNT.into(),
+        TOKEN_LIQUIDITY.into(),
+        deadline::<T>(),
+        caller.clone()
+    )
+    verify {
+        assert_eq!(
+            <zenlink_assets::Module<T>>::balance_of(&other_token_id, &caller),
+            other_token_balance + TOKEN_AMOUNT.into()
+        );
+    }
+
+    create_pair {
+        let caller = funded_caller::<T>();
+        let token_a = issue_token::<T>(&caller);
+        let token_b = issue_token::<T>(&caller);
+    }: _(RawOrigin::Signed(caller), token_a, token_b)
+    verify {
+        assert!(Module::<T>::tokens_to_pair(Module::<T>::sort_tokens(token_a, token_b)).is_some());
+    }
+
+    add_pair_liquidity {
+        let caller = funded_caller::<T>();
+        let token_a = issue_token::<T>(&caller);
+        let token_b = issue_token::<T>(&caller);
+        let pair_id = setup_pair::<T>(&caller, token_a, token_b)?;
+        let reserve_a = Module::<T>::get_pair_reserve(&Module::<T>::get_pair(pair_id).unwrap(), token_a);
+        next_block::<T>();
+    }: _(
+        RawOrigin::Signed(caller),
+        token_a,
+        token_b,
+        TOKEN_AMOUNT.into(),
+        Zero::zero(),
+        TOKEN_LIQUIDITY.into(),
+        deadline::<T>()
+    )
+    verify {
+        assert_eq!(
+            Module::<T>::get_pair_reserve(&Module::<T>::get_pair(pair_id).unwrap(), token_a),
+            reserve_a + TOKEN_AMOUNT.into()
+        );
+    }
+
+    remove_pair_liquidity {
+        let caller = funded_caller::<T>();
+        let token_a = issue_token::<T>(&caller);
+        let token_b = issue_token::<T>(&caller);
+        let pair_id = setup_pair::<T>(&caller, token_a, token_b)?;
+        let reserve_a = Module::<T>::get_pair_reserve(&Module::<T>::get_pair(pair_id).unwrap(), token_a);
+        next_block::<T>();
+    }: _(
+        RawOrigin::Signed(caller),
+        token_a,
+        token_b,
+        TOKEN_AMOUNT.into(),
+        Zero::zero(),
+        Zero::zero(),
+        deadline::<T>()
+    )
+    verify {
+        assert!(Module::<T>::get_pair_reserve(&Module::<T>::get_pair(pair_id).unwrap(), token_a) < reserve_a);
+    }
+
+    token_to_token_pair_input {
+        let caller = funded_caller::<T>();
+        let token_a = issue_token::<T>(&caller);
+        let token_b = issue_token::<T>(&caller);
+        setup_pair::<T>(&caller, token_a, token_b)?;
+        let balance_b = <zenlink_assets::Module<T>>::balance_of(&token_b, &caller);
+        next_block::<T>();
+    }: _(
+        RawOrigin::Signed(caller.clone()),
+        token_a,
+        token_b,
+        TOKEN_AMOUNT.into(),
+        One::one(),
+        deadline::<T>(),
+        caller.clone()
+    )
+    verify {
+        assert!(<zenlink_assets::Module<T>>::balance_of(&token_b, &caller) > balance_b);
+    }
+
+    token_to_token_pair_output {
+        let caller = funded_caller::<T>();
+        let token_a = issue_token::<T>(&caller);
+        let token_b = issue_token::<T>(&caller);
+        setup_pair::<T>(&caller, token_a, token_b)?;
+        let balance_b = <zenlink_assets::Module<T>>::balance_of(&token_b, &caller);
+        next_block::<T>();
+    }: _(
+        RawOrigin::Signed(caller.clone()),
+        token_a,
+        token_b,
+        TOKEN_AMOUNT.into(),
+        TOKEN_LIQUIDITY.into(),
+        deadline::<T>(),
+        caller.clone()
+    )
+    verify {
+        assert_eq!(<zenlink_assets::Module<T>>::balance_of(&token_b, &caller), balance_b + TOKEN_AMOUNT.into());
+    }
+
+    swap_exact_in_by_path {
+        let p in 1 .. MAX_PATH;
+        let caller = funded_caller::<T>();
+        let (token_in, path) = setup_path::<T>(&caller, p)?;
+        let balance_in = <zenlink_assets::Module<T>>::balance_of(&token_in, &caller);
+        next_block::<T>();
+    }: _(
+        RawOrigin::Signed(caller.clone()),
+        SwapAsset::Token(token_in),
+        path,
+        TOKEN_AMOUNT.into(),
+        One::one(),
+        deadline::<T>(),
+        caller.clone()
+    )
+    verify {
+        assert_eq!(<zenlink_assets::Module<T>>::balance_of(&token_in, &caller), balance_in - TOKEN_AMOUNT.into());
+    }
+
+    swap_exact_out_by_path {
+        let p in 1 .. MAX_PATH;
+        let caller = funded_caller::<T>();
+        let (token_in, path) = setup_path::<T>(&caller, p)?;
+        let balance_in = <zenlink_assets::Module<T>>::balance_of(&token_in, &caller);
+        next_block::<T>();
+    }: _(
+        RawOrigin::Signed(caller.clone()),
+        SwapAsset::Token(token_in),
+        path,
+        TOKEN_AMOUNT.into(),
+        TOKEN_LIQUIDITY.into(),
+        deadline::<T>(),
+        caller.clone()
+    )
+    verify {
+        assert!(<zenlink_assets::Module<T>>::balance_of(&token_in, &caller) < balance_in);
+    }
+
+    sync {
+        let caller = funded_caller::<T>();
+        let (token_id, exchange_id) = setup_exchange::<T>(&caller)?;
+        let account = Module::<T>::get_exchange(exchange_id).unwrap().account;
+        T::Currency::transfer(&caller, &account, currency::<T>(CURRENCY_AMOUNT), ExistenceRequirement::KeepAlive)?;
+        <zenlink_assets::Module<T>>::inner_transfer(&token_id, &caller, &account, TOKEN_AMOUNT.into())?;
+        next_block::<T>();
+    }: _(RawOrigin::Signed(caller), SwapHandler::ExchangeId(exchange_id))
+    verify {
+        let exchange = Module::<T>::get_exchange(exchange_id).unwrap();
+        assert_eq!(exchange.currency_reserve, T::Currency::free_balance(&account));
+    }
+
+    skim {
+        let caller = funded_caller::<T>();
+        let (token_id, exchange_id) = setup_exchange::<T>(&caller)?;
+        let account = Module::<T>::get_exchange(exchange_id).unwrap().account;
+        T::Currency::transfer(&caller, &account, currency::<T>(CURRENCY_AMOUNT), ExistenceRequirement::KeepAlive)?;
+        <zenlink_assets::Module<T>>::inner_transfer(&token_id, &caller, &account, TOKEN_AMOUNT.into())?;
+    }: _(RawOrigin::Signed(caller.clone()), SwapHandler::ExchangeId(exchange_id), caller.clone())
+    verify {
+        let exchange = Module::<T>::get_exchange(exchange_id).unwrap();
+        assert_eq!(exchange.currency_reserve, T::Currency::free_balance(&account));
+    }
+
+    close_exchange {
+        let caller = funded_caller::<T>();
+        let (_, exchange_id) = setup_exchange::<T>(&caller)?;
+        let liquidity_id = Module::<T>::get_exchange(exchange_id).unwrap().liquidity_id;
+        // Leave the balances in the exchange account to sweep, with no liquidity held by providers.
+        for holder in [caller.clone(), account::<T::AccountId>("fee_to", 0, SEED)].iter() {
+            let liquidity = <zenlink_assets::Module<T>>::balance_of(&liquidity_id, holder);
+            <zenlink_assets::Module<T>>::inner_burn(&liquidity_id, holder, liquidity)?;
+        }
+    }: _(RawOrigin::Signed(caller.clone()), SwapHandler::ExchangeId(exchange_id), caller.clone())
+    verify {
+        assert!(Module::<T>::get_exchange(exchange_id).is_none());
+    }
+
+    force_close_exchange {
+        let caller = funded_caller::<T>();
+        let (_, exchange_id) = setup_exchange::<T>(&caller)?;
+        let origin = T::ControlOrigin::successful_origin();
+    }: {
+        Module::<T>::force_close_exchange(origin, SwapHandler::ExchangeId(exchange_id), caller.clone())?;
+    }
+    verify {
+        assert!(Module::<T>::get_exchange(exchange_id).is_none());
+    }
+
+    // The repayment is made before the call, so the benchmark doesn't depend on the `FlashSwapHandler`.
+    flash_swap {
+        let caller = funded_caller::<T>();
+        let (token_id, exchange_id) = setup_exchange::<T>(&caller)?;
+        let account = Module::<T>::get_exchange(exchange_id).unwrap().account;
+        T::Currency::transfer(&caller, &account, currency::<T>(CURRENCY_AMOUNT * 11 / 10), ExistenceRequirement::KeepAlive)?;
+        <zenlink_assets::Module<T>>::inner_transfer(&token_id, &caller, &account, (TOKEN_AMOUNT * 11 / 10).into())?;
+        next_block::<T>();
+    }: _(
+        RawOrigin::Signed(caller.clone()),
+        SwapHandler::ExchangeId(exchange_id),
+        currency::<T>(CURRENCY_AMOUNT),
+        TOKEN_AMOUNT.into(),
+        caller.clone(),
+        Vec::new()
+    )
+    verify {
+        let exchange = Module::<T>::get_exchange(exchange_id).unwrap();
+        assert_eq!(exchange.currency_reserve, T::Currency::free_balance(&account));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{new_test_ext, Test};
+    use frame_support::assert_ok;
+
+    #[test]
+    fn test_benchmarks() {
+        new_test_ext().execute_with(|| {
+            assert_ok!(test_benchmark_create_exchange::<Test>());
+            assert_ok!(test_benchmark_set_exchange_fee_rate::<Test>());
+            assert_ok!(test_benchmark_set_fee_to::<Test>());
+            assert_ok!(test_benchmark_set_exchange_status::<Test>());
+            assert_ok!(test_benchmark_set_global_status::<Test>());
+            assert_ok!(test_benchmark_add_liquidity::<Test>());
+            assert_ok!(test_benchmark_add_liquidity_by_amounts::<Test>());
+            assert_ok!(test_benchmark_remove_liquidity::<Test>());
+            assert_ok!(test_benchmark_add_liquidity_single_sided::<Test>());
+            assert_ok!(test_benchmark_remove_liquidity_single_sided::<Test>());
+            assert_ok!(test_benchmark_currency_to_token_input::<Test>());
+            assert_ok!(test_benchmark_currency_to_token_output::<Test>());
+            assert_ok!(test_benchmark_token_to_currency_input::<Test>());
+            assert_ok!(test_benchmark_token_to_currency_output::<Test>());
+            assert_ok!(test_benchmark_token_to_token_input::<Test>());
+            assert_ok!(test_benchmark_token_to_token_output::<Test>());
+            assert_ok!(test_benchmark_create_pair::<Test>());
+            assert_ok!(test_benchmark_add_pair_liquidity::<Test>());
+            assert_ok!(test_benchmark_remove_pair_liquidity::<Test>());
+            assert_ok!(test_benchmark_token_to_token_pair_input::<Test>());
+            assert_ok!(test_benchmark_token_to_token_pair_output::<Test>());
+            assert_ok!(test_benchmark_swap_exact_in_by_path::<Test>());
+            assert_ok!(test_benchmark_swap_exact_out_by_path::<Test>());
+            assert_ok!(test_benchmark_sync::<Test>());
+            assert_ok!(test_benchmark_skim::<Test>());
+            assert_ok!(test_benchmark_close_exchange::<Test>());
+            assert_ok!(test_benchmark_force_close_exchange::<Test>());
+            assert_ok!(test_benchmark_flash_swap::<Test>());
+        });
+    }
+}
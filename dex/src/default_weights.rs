@@ -0,0 +1,163 @@
+//! Default weights of the dex module.
+//!
+//! NOTE: these are placeholders estimated by hand from the storage accesses of every call, they are
+//! not benchmark output and must be replaced by the `WeightInfo` impl generated from
+//! `src/benchmarking.rs` before they are relied on. The calls changing the reserves are benchmarked
+//! with the most price observations kept, at most `MAX_PRICE_OBSERVATIONS`. Generate them on the
+//! reference hardware by a node built with the `runtime-benchmarks` feature:
+//!
+//! ```text
+//! node benchmark --chain dev --execution wasm --wasm-execution compiled \
+//!     --pallet zenlink_dex --extrinsic '*' --steps 50 --repeat 20 \
+//!     --output dex/src/default_weights.rs
+//! ```
+
+use frame_support::weights::{constants::RocksDbWeight as DbWeight, Weight};
+
+impl crate::WeightInfo for () {
+    fn create_exchange() -> Weight {
+        (52_000_000 as Weight)
+            .saturating_add(DbWeight::get().reads(5 as Weight))
+            .saturating_add(DbWeight::get().writes(8 as Weight))
+    }
+    fn set_exchange_fee_rate() -> Weight {
+        (24_000_000 as Weight)
+            .saturating_add(DbWeight::get().reads(2 as Weight))
+            .saturating_add(DbWeight::get().writes(1 as Weight))
+    }
+    fn set_fee_to() -> Weight {
+        (11_000_000 as Weight)
+            .saturating_add(DbWeight::get().reads(0 as Weight))
+            .saturating_add(DbWeight::get().writes(1 as Weight))
+    }
+    fn set_exchange_status() -> Weight {
+        (26_000_000 as Weight)
+            .saturating_add(DbWeight::get().reads(3 as Weight))
+            .saturating_add(DbWeight::get().writes(1 as Weight))
+    }
+    fn set_global_status() -> Weight {
+        (12_000_000 as Weight)
+            .saturating_add(DbWeight::get().writes(1 as Weight))
+    }
+    fn add_liquidity() -> Weight {
+        (148_000_000 as Weight)
+            .saturating_add(DbWeight::get().reads(14 as Weight))
+            .saturating_add(DbWeight::get().writes(12 as Weight))
+    }
+    fn add_liquidity_by_amounts() -> Weight {
+        (152_000_000 as Weight)
+            .saturating_add(DbWeight::get().reads(14 as Weight))
+            .saturating_add(DbWeight::get().writes(12 as Weight))
+    }
+    fn remove_liquidity() -> Weight {
+        (136_000_000 as Weight)
+            .saturating_add(DbWeight::get().reads(13 as Weight))
+            .saturating_add(DbWeight::get().writes(11 as Weight))
+    }
+    fn add_liquidity_single_sided() -> Weight {
+        (164_000_000 as Weight)
+            .saturating_add(DbWeight::get().reads(16 as Weight))
+            .saturating_add(DbWeight::get().writes(12 as Weight))
+    }
+    fn remove_liquidity_single_sided() -> Weight {
+        (152_000_000 as Weight)
+            .saturating_add(DbWeight::get().reads(15 as Weight))
+            .saturating_add(DbWeight::get().writes(11 as Weight))
+    }
+    fn currency_to_token_input() -> Weight {
+        (88_000_000 as Weight)
+            .saturating_add(DbWeight::get().reads(8 as Weight))
+            .saturating_add(DbWeight::get().writes(7 as Weight))
+    }
+    fn currency_to_token_output() -> Weight {
+        (90_000_000 as Weight)
+            .saturating_add(DbWeight::get().reads(8 as Weight))
+            .saturating_add(DbWeight::get().writes(7 as Weight))
+    }
+    fn token_to_currency_input() -> Weight {
+        (92_000_000 as Weight)
+            .saturating_add(DbWeight::get().reads(9 as Weight))
+            .saturating_add(DbWeight::get().writes(8 as Weight))
+    }
+    fn token_to_currency_output() -> Weight {
+        (94_000_000 as Weight)
+            .saturating_add(DbWeight::get().reads(9 as Weight))
+            .saturating_add(DbWeight::get().writes(8 as Weight))
+    }
+    fn token_to_token_input() -> Weight {
+        (142_000_000 as Weight)
+            .saturating_add(DbWeight::get().reads(13 as Weight))
+            .saturating_add(DbWeight::get().writes(12 as Weight))
+    }
+    fn token_to_token_output() -> Weight {
+        (146_000_000 as Weight)
+            .saturating_add(DbWeight::get().reads(13 as Weight))
+            .saturating_add(DbWeight::get().writes(12 as Weight))
+    }
+    fn create_pair() -> Weight {
+        (58_000_000 as Weight)
+            .saturating_add(DbWeight::get().reads(7 as Weight))
+            .saturating_add(DbWeight::get().writes(8 as Weight))
+    }
+    fn add_pair_liquidity() -> Weight {
+        (156_000_000 as Weight)
+            .saturating_add(DbWeight::get().reads(15 as Weight))
+            .saturating_add(DbWeight::get().writes(13 as Weight))
+    }
+    fn remove_pair_liquidity() -> Weight {
+        (140_000_000 as Weight)
+            .saturating_add(DbWeight::get().reads(13 as Weight))
+            .saturating_add(DbWeight::get().writes(12 as Weight))
+    }
+    fn token_to_token_pair_input() -> Weight {
+        (90_000_000 as Weight)
+            .saturating_add(DbWeight::get().reads(9 as Weight))
+            .saturating_add(DbWeight::get().writes(7 as Weight))
+    }
+    fn token_to_token_pair_output() -> Weight {
+        (92_000_000 as Weight)
+            .saturating_add(DbWeight::get().reads(9 as Weight))
+            .saturating_add(DbWeight::get().writes(7 as Weight))
+    }
+    fn swap_exact_in_by_path(p: u32) -> Weight {
+        (34_000_000 as Weight)
+            .saturating_add((78_000_000 as Weight).saturating_mul(p as Weight))
+            .saturating_add(DbWeight::get().reads(2 as Weight))
+            .saturating_add(DbWeight::get().reads((8 as Weight).saturating_mul(p as Weight)))
+            .saturating_add(DbWeight::get().writes(1 as Weight))
+            .saturating_add(DbWeight::get().writes((7 as Weight).saturating_mul(p as Weight)))
+    }
+    fn swap_exact_out_by_path(p: u32) -> Weight {
+        (34_000_000 as Weight)
+            .saturating_add((78_000_000 as Weight).saturating_mul(p as Weight))
+            .saturating_add(DbWeight::get().reads(2 as Weight))
+            .saturating_add(DbWeight::get().reads((8 as Weight).saturating_mul(p as Weight)))
+            .saturating_add(DbWeight::get().writes(1 as Weight))
+            .saturating_add(DbWeight::get().writes((7 as Weight).saturating_mul(p as Weight)))
+    }
+    fn sync() -> Weight {
+        (46_000_000 as Weight)
+            .saturating_add(DbWeight::get().reads(5 as Weight))
+            .saturating_add(DbWeight::get().writes(4 as Weight))
+    }
+    fn skim() -> Weight {
+        (62_000_000 as Weight)
+            .saturating_add(DbWeight::get().reads(6 as Weight))
+            .saturating_add(DbWeight::get().writes(5 as Weight))
+    }
+    fn close_exchange() -> Weight {
+        (98_000_000 as Weight)
+            .saturating_add(DbWeight::get().reads(9 as Weight))
+            .saturating_add(DbWeight::get().writes(13 as Weight))
+    }
+    fn force_close_exchange() -> Weight {
+        (96_000_000 as Weight)
+            .saturating_add(DbWeight::get().reads(8 as Weight))
+            .saturating_add(DbWeight::get().writes(13 as Weight))
+    }
+    fn flash_swap() -> Weight {
+        (104_000_000 as Weight)
+            .saturating_add(DbWeight::get().reads(9 as Weight))
+            .saturating_add(DbWeight::get().writes(8 as Weight))
+    }
+}
@@ -10,26 +10,36 @@
 
 use codec::{Decode, Encode};
 use frame_support::{
-    decl_error, decl_event, decl_module, decl_storage, dispatch, ensure,
+    decl_error, decl_event, decl_module, decl_storage, dispatch, ensure, transactional,
     Parameter,
-    traits::{Currency, ExistenceRequirement, Get},
+    traits::{Currency, EnsureOrigin, ExistenceRequirement, Get},
+    weights::Weight,
 };
 use frame_system::ensure_signed;
 #[cfg(feature = "std")]
 use serde::{Deserialize, Serialize};
-use sp_runtime::{ModuleId, RuntimeDebug};
+use sp_core::U256;
+use sp_runtime::{FixedPointNumber, FixedU128, ModuleId, Permill, RuntimeDebug};
+use sp_std::{convert::{TryFrom, TryInto}, vec::Vec};
 use sp_runtime::traits::{
-    AccountIdConversion, AtLeast32Bit, CheckedAdd, MaybeSerializeDeserialize, Member, One,
-    SaturatedConversion, Zero,
+    AccountIdConversion, AtLeast32Bit, CheckedAdd, CheckedSub, MaybeSerializeDeserialize, Member, One,
+    Saturating, Zero,
 };
 
-pub use rpc::{ExchangeInfo, TokenInfo};
+pub use rpc::{
+    paginate_exchanges, AmountQuote, ExchangeFilter, ExchangeInfo, ExchangeInfoV1, ExchangePage, LiquidityPosition, SwapKind,
+    SwapRoute, TokenInfo, MAX_EXCHANGES_PAGE_SIZE, MAX_ROUTE_HOPS,
+};
 use zenlink_assets::AssetInfo;
 
 #[cfg(test)]
 mod mock;
 #[cfg(test)]
 mod tests;
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+mod default_weights;
+mod migration;
 mod rpc;
 
 /// ZLK liquidity token info
@@ -40,21 +50,108 @@ const ZLK: &AssetInfo = &AssetInfo {
     decimals: 0u8,
 };
 
+/// The maximum number of exchanges and pairs in the path of a swap.
+pub const MAX_PATH: u32 = 5;
+
+/// The upper bound of `MaxPriceObservations`, the weights are benchmarked with it.
+pub const MAX_PRICE_OBSERVATIONS: u32 = 100;
+
+/// The amount of a `SwapAsset`, which holds both the currency balance and the token balance.
+pub type AssetBalance = u128;
+
 /// The Dex main structure
 #[derive(Encode, Decode, Eq, PartialEq, Clone, RuntimeDebug, Default)]
 #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
-pub struct Exchange<AccountId, AssetId> {
+pub struct Exchange<AccountId, AssetId, TokenBalance, Balance> {
     // The token being swapped.
     pub token_id: AssetId,
     // The exchange liquidity asset.
     pub liquidity_id: AssetId,
     // This exchange account.
     pub account: AccountId,
+    // The fee rate of this exchange, `None` means using the default `ExchangeFeeRate`.
+    // Not in the `Releases::V1` layout, `migrate_to_v2` sets it to `None`.
+    pub fee_rate: Option<Permill>,
+    // The token reserve of this exchange, only changed by the dex operations.
+    pub token_reserve: TokenBalance,
+    // The currency reserve of this exchange, only changed by the dex operations.
+    // The reserves aren't in the `Releases::V1` layout, `migrate_to_v2` takes them from the
+    // balances of the exchange account.
+    pub currency_reserve: Balance,
+}
+
+/// The token to token trade pair, sharing the identifier space with `Exchange`
+#[derive(Encode, Decode, Eq, PartialEq, Clone, RuntimeDebug, Default)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct Pair<AccountId, AssetId, TokenBalance> {
+    // The token with the smaller asset id.
+    pub token_0: AssetId,
+    // The token with the larger asset id.
+    pub token_1: AssetId,
+    // The pair liquidity asset.
+    pub liquidity_id: AssetId,
+    // This pair account.
+    pub account: AccountId,
+    // The fee rate of this pair, `None` means using the default `ExchangeFeeRate`.
+    pub fee_rate: Option<Permill>,
+    // The token_0 reserve of this pair, only changed by the dex operations.
+    pub reserve_0: TokenBalance,
+    // The token_1 reserve of this pair, only changed by the dex operations.
+    pub reserve_1: TokenBalance,
+}
+
+/// The cumulative prices of an exchange or pair, the asset 0 is the currency of an exchange
+/// or the token_0 of a pair, the asset 1 is the token of an exchange or the token_1 of a pair.
+#[derive(Encode, Decode, Eq, PartialEq, Clone, RuntimeDebug, Default)]
+pub struct PriceCumulative<BlockNumber> {
+    // The sum of the asset 0 price in asset 1 of every block, in `FixedU128` accuracy.
+    pub price_0_cumulative: U256,
+    // The sum of the asset 1 price in asset 0 of every block, in `FixedU128` accuracy.
+    pub price_1_cumulative: U256,
+    // The block of the last accumulation.
+    pub block_last: BlockNumber,
+}
+
+/// The trading status of an exchange or pair, or of all of them
+#[derive(Encode, Decode, Eq, PartialEq, Ord, PartialOrd, Clone, Copy, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum ExchangeStatus {
+    /// All operations are allowed.
+    Active,
+    /// Swaps are paused, adding and removing liquidity are still allowed.
+    SwapsPaused,
+    /// Only removing liquidity is allowed.
+    WithdrawOnly,
+}
+
+impl Default for ExchangeStatus {
+    fn default() -> Self {
+        ExchangeStatus::Active
+    }
+}
+
+/// The storage layout versions of the dex module
+#[derive(Encode, Decode, Eq, PartialEq, Clone, Copy, RuntimeDebug)]
+pub enum Releases {
+    /// The first release: the exchange maps hashed by `opaque_blake2_256`, the exchanges without
+    /// the fee rate and the reserves, no pairs.
+    V1,
+    /// The maps hashed by `twox_64_concat`, which can be iterated, the exchanges with the fee rate
+    /// and the reserves. Every layout change since `V1` is migrated to it at once.
+    V2,
+}
+
+impl Default for Releases {
+    fn default() -> Self {
+        Releases::V1
+    }
 }
 
 /// The wrapper of exchangeId and assetId to access
 #[derive(Encode, Decode, Eq, PartialEq, Clone, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
 pub enum SwapHandler<ExchangeId, AssetId> {
     ExchangeId(ExchangeId),
     AssetId(AssetId),
@@ -70,14 +167,114 @@ impl<ExchangeId, AssetId> SwapHandler <ExchangeId, AssetId> {
     }
 }
 
+/// The asset to be swapped, the native currency or a token
+#[derive(Encode, Decode, Eq, PartialEq, Clone, Copy, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum SwapAsset<AssetId> {
+    Currency,
+    Token(AssetId),
+}
+
+/// The callback of the flash swap, called after the borrowed currency and token were sent to
+/// the receiver. It must repay the exchange account with the fee before returning.
+pub trait FlashSwapHandler<AccountId, ExchangeId, Balance, TokenBalance> {
+    fn on_flash_swap(
+        sender: &AccountId,
+        receiver: &AccountId,
+        exchange_id: ExchangeId,
+        currency_amount: Balance,
+        token_amount: TokenBalance,
+        data: &[u8],
+    ) -> dispatch::DispatchResult;
+
+    /// The weight of `on_flash_swap` with the data, added to the weight of `flash_swap`.
+    fn on_flash_swap_weight(data: &[u8]) -> Weight;
+}
+
+impl<AccountId, ExchangeId, Balance, TokenBalance> FlashSwapHandler<AccountId, ExchangeId, Balance, TokenBalance> for () {
+    fn on_flash_swap(
+        _sender: &AccountId,
+        _receiver: &AccountId,
+        _exchange_id: ExchangeId,
+        _currency_amount: Balance,
+        _token_amount: TokenBalance,
+        _data: &[u8],
+    ) -> dispatch::DispatchResult {
+        Ok(())
+    }
+
+    fn on_flash_swap_weight(_data: &[u8]) -> Weight {
+        0
+    }
+}
+
+/// A resolved hop of the swap path
+struct SwapHop<T: Trait> {
+    // The exchange or pair to swap in.
+    exchange_id: T::ExchangeId,
+    // The exchange or pair account.
+    account: T::AccountId,
+    asset_in: SwapAssetOf<T>,
+    asset_out: SwapAssetOf<T>,
+    reserve_in: U256,
+    reserve_out: U256,
+    fee_rate: Permill,
+}
+
 type BalanceOf<T> =
     <<T as Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::Balance;
 
 type TokenBalance<T> = <T as zenlink_assets::Trait>::TokenBalance;
 
+type ExchangeOf<T> = Exchange<
+    <T as frame_system::Trait>::AccountId,
+    <T as zenlink_assets::Trait>::AssetId,
+    TokenBalance<T>,
+    BalanceOf<T>,
+>;
+
+type PairOf<T> = Pair<
+    <T as frame_system::Trait>::AccountId,
+    <T as zenlink_assets::Trait>::AssetId,
+    TokenBalance<T>,
+>;
+
 type SwapHandlerOf<T> =
     SwapHandler<<T as Trait>::ExchangeId, <T as zenlink_assets::Trait>::AssetId>;
 
+type SwapAssetOf<T> = SwapAsset<<T as zenlink_assets::Trait>::AssetId>;
+
+pub trait WeightInfo {
+    fn create_exchange() -> Weight;
+    fn set_exchange_fee_rate() -> Weight;
+    fn set_fee_to() -> Weight;
+    fn set_exchange_status() -> Weight;
+    fn set_global_status() -> Weight;
+    fn add_liquidity() -> Weight;
+    fn add_liquidity_by_amounts() -> Weight;
+    fn remove_liquidity() -> Weight;
+    fn add_liquidity_single_sided() -> Weight;
+    fn remove_liquidity_single_sided() -> Weight;
+    fn currency_to_token_input() -> Weight;
+    fn currency_to_token_output() -> Weight;
+    fn token_to_currency_input() -> Weight;
+    fn token_to_currency_output() -> Weight;
+    fn token_to_token_input() -> Weight;
+    fn token_to_token_output() -> Weight;
+    fn create_pair() -> Weight;
+    fn add_pair_liquidity() -> Weight;
+    fn remove_pair_liquidity() -> Weight;
+    fn token_to_token_pair_input() -> Weight;
+    fn token_to_token_pair_output() -> Weight;
+    fn swap_exact_in_by_path(p: u32) -> Weight;
+    fn swap_exact_out_by_path(p: u32) -> Weight;
+    fn sync() -> Weight;
+    fn skim() -> Weight;
+    fn flash_swap() -> Weight;
+    fn close_exchange() -> Weight;
+    fn force_close_exchange() -> Weight;
+}
+
 /// The pallet's configuration trait.
 pub trait Trait: frame_system::Trait + zenlink_assets::Trait {
     /// The overarching event type.
@@ -88,18 +285,74 @@ pub trait Trait: frame_system::Trait + zenlink_assets::Trait {
     type Currency: Currency<Self::AccountId>;
     /// The dex's module id, used for deriving sovereign account IDs.
     type ModuleId: Get<ModuleId>;
+    /// The default fee rate charged on the input of every swap.
+    type ExchangeFeeRate: Get<Permill>;
+    /// The origin which may change the parameters of the exchanges.
+    type ControlOrigin: EnsureOrigin<Self::Origin>;
+    /// The liquidity locked forever on the first deposit of every exchange and pair.
+    type MinimumLiquidity: Get<<Self as zenlink_assets::Trait>::TokenBalance>;
+    /// The maximum number of price observations kept for every exchange and pair, capped by
+    /// `MAX_PRICE_OBSERVATIONS`.
+    type MaxPriceObservations: Get<u32>;
+    /// Weight information for extrinsics in this pallet.
+    type WeightInfo: WeightInfo;
+    /// The callback repaying the flash swaps, its weight is added to the weight of `flash_swap`.
+    type FlashSwapHandler: FlashSwapHandler<
+        Self::AccountId,
+        Self::ExchangeId,
+        BalanceOf<Self>,
+        <Self as zenlink_assets::Trait>::TokenBalance,
+    >;
 }
 
 decl_storage! {
     trait Store for Module<T: Trait> as DexStorage {
         /// Token to exchange: asset_id -> exchange_id
-        TokenToExchange get(fn token_to_exchange): map hasher(opaque_blake2_256) T::AssetId => Option<T::ExchangeId>;
-        /// Liquidity to exchange: zlk_asset_id -> exchange_id
-        ZLKToExchange get(fn zlk_to_exchange): map hasher(opaque_blake2_256) T::AssetId => Option<T::ExchangeId>;
+        TokenToExchange get(fn token_to_exchange): map hasher(twox_64_concat) T::AssetId => Option<T::ExchangeId>;
+        /// Tokens to pair: (smaller_asset_id, larger_asset_id) -> exchange_id
+        TokensToPair get(fn tokens_to_pair): map hasher(twox_64_concat) (T::AssetId, T::AssetId) => Option<T::ExchangeId>;
+        /// Liquidity to exchange or pair: zlk_asset_id -> exchange_id
+        ZLKToExchange get(fn zlk_to_exchange): map hasher(twox_64_concat) T::AssetId => Option<T::ExchangeId>;
         /// The exchanges: exchange_id -> exchange
-        Exchanges get(fn get_exchange): map hasher(opaque_blake2_256) T::ExchangeId => Option<Exchange<T::AccountId, T::AssetId>>;
-        /// The next exchange identifier
+        Exchanges get(fn get_exchange): map hasher(twox_64_concat) T::ExchangeId => Option<ExchangeOf<T>>;
+        /// The token to token pairs: exchange_id -> pair
+        Pairs get(fn get_pair): map hasher(twox_64_concat) T::ExchangeId => Option<PairOf<T>>;
+        /// The next exchange identifier, shared by exchanges and pairs
         NextExchangeId get(fn next_exchange_id): T::ExchangeId;
+        /// The account receiving the protocol fee, the protocol fee is off when it's `None`.
+        FeeTo get(fn fee_to): Option<T::AccountId>;
+        /// The reserves product(k) as of immediately after the most recent liquidity event: exchange_id -> k
+        KLast get(fn k_last): map hasher(twox_64_concat) T::ExchangeId => U256;
+        /// The cumulative prices, accumulated on the first reserves change in each block: exchange_id -> cumulative
+        PriceCumulatives get(fn price_cumulative): map hasher(twox_64_concat) T::ExchangeId => PriceCumulative<T::BlockNumber>;
+        /// The recent cumulative prices, the oldest is the first: exchange_id -> observations
+        PriceObservations get(fn price_observations): map hasher(twox_64_concat) T::ExchangeId => Vec<PriceCumulative<T::BlockNumber>>;
+        /// The status of the exchanges and pairs: exchange_id -> status
+        ExchangeStatuses get(fn exchange_status): map hasher(twox_64_concat) T::ExchangeId => ExchangeStatus;
+        /// The status of all exchanges and pairs, the stricter one of it and their own status applies.
+        GlobalStatus get(fn global_status): ExchangeStatus;
+        /// The exchanges locked by the flash swap in progress, no call can touch them until the
+        /// repayment is checked: exchange_id -> locked
+        FlashSwapLocks get(fn flash_swap_locked): map hasher(twox_64_concat) T::ExchangeId => bool;
+        /// The storage layout version, new chains start with the latest one.
+        StorageVersion get(fn storage_version) build(|_| Releases::V2): Releases;
+    }
+    add_extra_genesis {
+        /// The exchanges created at genesis, with the first liquidity provided by the holder, who must own
+        /// the currency and token: (token_id, currency_amount, token_amount, liquidity_holder)
+        config(exchanges): Vec<(T::AssetId, BalanceOf<T>, TokenBalance<T>, T::AccountId)>;
+        build(|config: &GenesisConfig<T>| {
+            for (token_id, currency_amount, token_amount, holder) in config.exchanges.iter() {
+                let exchange_id = <Module<T>>::do_create_exchange(*token_id)
+                    .unwrap_or_else(|e| panic!(
+                        "Failed to create the genesis exchange of the token {:?}: {:?}", token_id, e
+                    ));
+                <Module<T>>::do_add_liquidity(holder, exchange_id, *currency_amount, Zero::zero(), *token_amount)
+                    .unwrap_or_else(|e| panic!(
+                        "Failed to add the genesis liquidity of the token {:?} by {:?}: {:?}", token_id, holder, e
+                    ));
+            }
+        });
     }
 }
 
@@ -109,6 +362,7 @@ decl_event! {
        BalanceOf = BalanceOf<T>,
        Id = <T as Trait>::ExchangeId,
        TokenBalance = <T as zenlink_assets::Trait>::TokenBalance,
+       AssetId = <T as zenlink_assets::Trait>::AssetId,
     {
         /// An exchange was created. \[ExchangeId, ExchangeAccount\]
         ExchangeCreated(Id, AccountId),
@@ -116,12 +370,52 @@ decl_event! {
         LiquidityAdded(Id, AccountId, BalanceOf, TokenBalance),
         /// Remove liquidity from the exchange success. \[ExchangeId, ExchangeAccount, Currency_output, Token_output\]
         LiquidityRemoved(Id, AccountId, BalanceOf, TokenBalance),
+        /// Add liquidity with a single asset success. \[ExchangeId, Provider, Asset_in, Amount_in, Liquidity_minted\]
+        LiquidityAddedSingleSided(Id, AccountId, SwapAsset<AssetId>, AssetBalance, TokenBalance),
+        /// Remove liquidity in a single asset success. \[ExchangeId, Provider, Asset_out, Liquidity_burned, Amount_out\]
+        LiquidityRemovedSingleSided(Id, AccountId, SwapAsset<AssetId>, TokenBalance, AssetBalance),
         /// Use supply token to swap currency. \[ExchangeId, Buyer, Currency_bought, token_sold, Recipient\]
         CurrencyPurchase(Id, AccountId, BalanceOf, TokenBalance, AccountId),
         /// Use supply currency to swap token. \[ExchangeId, Buyer, Currency_sold, Tokens_bought, Recipient\]
         TokenPurchase(Id, AccountId, BalanceOf, TokenBalance, AccountId),
         /// Use supply token to swap other token. \[ExchangeId, Other_ExchangeId, Buyer, token_sold, other_token_bought, Recipient\]
         OtherTokenPurchase(Id, Id, AccountId, TokenBalance, TokenBalance, AccountId),
+        /// A token to token pair was created. \[ExchangeId, PairAccount, Token_0, Token_1\]
+        PairCreated(Id, AccountId, AssetId, AssetId),
+        /// Add liquidity to the pair success. \[ExchangeId, Provider, Token_a, Token_a_input, Token_b, Token_b_input\]
+        PairLiquidityAdded(Id, AccountId, AssetId, TokenBalance, AssetId, TokenBalance),
+        /// Remove liquidity from the pair success. \[ExchangeId, Provider, Token_a, Token_a_output, Token_b, Token_b_output\]
+        PairLiquidityRemoved(Id, AccountId, AssetId, TokenBalance, AssetId, TokenBalance),
+        /// Use supply token to swap other token in the pair. \[ExchangeId, Buyer, Token, Token_sold, Other_token, Other_token_bought, Recipient\]
+        PairTokenPurchase(Id, AccountId, AssetId, TokenBalance, AssetId, TokenBalance, AccountId),
+        /// Swap in a hop of the path. \[ExchangeId, Asset_sold, Amount_sold, Asset_bought, Amount_bought\]
+        PathHopSwapped(Id, SwapAsset<AssetId>, AssetBalance, SwapAsset<AssetId>, AssetBalance),
+        /// Swap along the path success. \[Buyer, Asset_sold, Amount_sold, Asset_bought, Amount_bought, Recipient\]
+        PathSwapped(AccountId, SwapAsset<AssetId>, AssetBalance, SwapAsset<AssetId>, AssetBalance, AccountId),
+        /// The fee rate of the exchange was changed. \[ExchangeId, New_fee_rate\]
+        ExchangeFeeRateChanged(Id, Option<Permill>),
+        /// The protocol fee receiver was changed. \[New_fee_to\]
+        FeeToChanged(Option<AccountId>),
+        /// Protocol fee was minted in liquidity token. \[ExchangeId, FeeTo, Liquidity_minted\]
+        ProtocolFeeMinted(Id, AccountId, TokenBalance),
+        /// The reserves of the exchange were synced to its balances. \[ExchangeId, Currency_reserve, Token_reserve\]
+        ExchangeSynced(Id, BalanceOf, TokenBalance),
+        /// The reserves of the pair were synced to its balances. \[ExchangeId, Reserve_0, Reserve_1\]
+        PairSynced(Id, TokenBalance, TokenBalance),
+        /// The surplus over the reserves of the exchange was skimmed. \[ExchangeId, Recipient, Currency_surplus, Token_surplus\]
+        ExchangeSkimmed(Id, AccountId, BalanceOf, TokenBalance),
+        /// The surplus over the reserves of the pair was skimmed. \[ExchangeId, Recipient, Surplus_0, Surplus_1\]
+        PairSkimmed(Id, AccountId, TokenBalance, TokenBalance),
+        /// The status of the exchange or pair was changed. \[ExchangeId, New_status\]
+        ExchangeStatusChanged(Id, ExchangeStatus),
+        /// The status of all exchanges and pairs was changed. \[New_status\]
+        GlobalStatusChanged(ExchangeStatus),
+        /// The exchange was closed and its balances were swept. \[ExchangeId, Recipient, Currency_swept, Token_swept\]
+        ExchangeClosed(Id, AccountId, BalanceOf, TokenBalance),
+        /// The pair was closed and its balances were swept. \[ExchangeId, Recipient, Token_0_swept, Token_1_swept\]
+        PairClosed(Id, AccountId, TokenBalance, TokenBalance),
+        /// The flash swap was repaid. \[ExchangeId, Sender, Receiver, Currency_borrowed, Token_borrowed, Currency_repaid, Token_repaid\]
+        FlashSwapped(Id, AccountId, AccountId, BalanceOf, TokenBalance, BalanceOf, TokenBalance),
     }
 }
 
@@ -161,13 +455,50 @@ decl_error! {
         /// Exchange would cost too much in token.
         TooExpensiveToken,
         /// The allowance token balance of exchange spend too low.
-        AllowanceLow
+        AllowanceLow,
+        /// The fee rate must be less than 100%.
+        InvalidFeeRate,
+        /// A pair can't be created with two identical tokens.
+        IdenticalTokens,
+        /// Pair not exists for these tokens.
+        PairNotExists,
+        /// A Pair already exists for these tokens.
+        PairAlreadyExists,
+        /// The swap path is empty, repeated or not connected.
+        InvalidPath,
+        /// Not enough output asset will be returned.
+        NotEnoughOutput,
+        /// Path swap would cost too much in input asset.
+        TooExpensiveInput,
+        /// The amount overflows in the exchange math.
+        Overflow,
+        /// The reserve is not enough for the amount to buy.
+        InsufficientReserve,
+        /// The first deposit must create more liquidity than the `MinimumLiquidity`.
+        BelowMinimumLiquidity,
+        /// The window of the average price must be non-zero.
+        InvalidWindow,
+        /// No price observation is old enough for the window.
+        NoPriceObservation,
+        /// Swaps are paused in the exchange or pair.
+        SwapsPaused,
+        /// Only removing liquidity is allowed in the exchange or pair.
+        WithdrawOnly,
+        /// The asset is neither the native currency nor the token of the exchange.
+        InvalidAsset,
+        /// The liquidity of the exchange is still held by providers.
+        LiquidityOutstanding,
+        /// Borrowed nothing in the flash swap.
+        ZeroFlashSwap,
+        /// The flash swap was not repaid with the fee.
+        FlashSwapNotRepaid,
+        /// The exchange is locked by the flash swap in progress.
+        Locked,
+        /// The swap path is longer than `MAX_PATH`.
+        PathTooLong,
     }
 }
 
-// TODO: weight
-// TODO: transaction
-// TODO: exchange fee
 // The pallet's dispatched functions.
 decl_module! {
     /// The module declaration.
@@ -176,39 +507,137 @@ decl_module! {
 
         fn deposit_event() = default;
 
+        fn on_runtime_upgrade() -> Weight {
+            if Self::storage_version() == Releases::V1 {
+                let weight = Self::migrate_to_v2();
+                StorageVersion::put(Releases::V2);
+
+                weight.saturating_add(T::DbWeight::get().reads_writes(1, 1))
+            } else {
+                T::DbWeight::get().reads(1)
+            }
+        }
+
         /// Create an exchange with the token which would swap with native currency
         ///
         /// - `token_id`: The exist asset's id.
-        #[weight = 0]
+        #[weight = T::WeightInfo::create_exchange()]
+        #[transactional]
         pub fn create_exchange(origin,
             token_id: T::AssetId,
         ) -> dispatch::DispatchResult
         {
-            let asset_info = <zenlink_assets::Module<T>>::asset_info(&token_id);
-            ensure!(asset_info.is_some(), Error::<T>::TokenNotExists);
-            ensure!(Self::zlk_to_exchange(token_id).is_none(), Error::<T>::DeniedSwap);
-            ensure!(Self::token_to_exchange(token_id).is_none(), Error::<T>::ExchangeAlreadyExists);
+            Self::do_create_exchange(token_id)?;
 
-            let exchange_id = Self::next_exchange_id();
-            let next_id = exchange_id.checked_add(&One::one())
-                .ok_or("Overflow")?;
+            Ok(())
+        }
 
-            let account: T::AccountId = T::ModuleId::get().into_sub_account(exchange_id);
+        /// Set the fee rate of specific exchange or pair, which overrides the default `ExchangeFeeRate`.
+        ///
+        /// The dispatch origin for this call must be `ControlOrigin`.
+        ///
+        /// - `swap_handler`: The wrapper of exchangeId and assetId to access, a pair is accessed by exchangeId.
+        /// - `fee_rate`: The new fee rate, `None` to use the default fee rate.
+        #[weight = T::WeightInfo::set_exchange_fee_rate()]
+        #[transactional]
+        pub fn set_exchange_fee_rate(origin,
+            swap_handler: SwapHandlerOf<T>,
+            fee_rate: Option<Permill>,
+        ) -> dispatch::DispatchResult
+        {
+            T::ControlOrigin::ensure_origin(origin)?;
 
-            // create a new lp token for exchange
-            let liquidity_id = <zenlink_assets::Module<T>>::inner_issue(&account, Zero::zero(), ZLK);
-            let new_exchange = Exchange {
-                token_id,
-                liquidity_id,
-                account: account.clone(),
-            };
+            if let Some(rate) = fee_rate {
+                ensure!(rate < Permill::one(), Error::<T>::InvalidFeeRate);
+            }
 
-            <TokenToExchange<T>>::insert(token_id, exchange_id);
-            <ZLKToExchange<T>>::insert(liquidity_id, exchange_id);
-            <Exchanges<T>>::insert(exchange_id, new_exchange);
-            <NextExchangeId<T>>::put(next_id);
+            let exchange_id = Self::get_exchange_id(&swap_handler)?;
 
-            Self::deposit_event(RawEvent::ExchangeCreated(exchange_id, account));
+            if <Pairs<T>>::contains_key(exchange_id) {
+                <Pairs<T>>::mutate(exchange_id, |maybe_pair| {
+                    if let Some(pair) = maybe_pair {
+                        pair.fee_rate = fee_rate;
+                    }
+                });
+            } else {
+                <Exchanges<T>>::try_mutate(exchange_id, |maybe_exchange| -> dispatch::DispatchResult {
+                    let exchange = maybe_exchange.as_mut().ok_or(Error::<T>::ExchangeNotExists)?;
+                    exchange.fee_rate = fee_rate;
+                    Ok(())
+                })?;
+            }
+
+            Self::deposit_event(RawEvent::ExchangeFeeRateChanged(exchange_id, fee_rate));
+
+            Ok(())
+        }
+
+        /// Set the status of specific exchange or pair, swaps are checked against the stricter one
+        /// of it and the global status.
+        ///
+        /// The dispatch origin for this call must be `ControlOrigin`.
+        ///
+        /// - `swap_handler`: The wrapper of exchangeId and assetId to access, a pair is accessed by exchangeId.
+        /// - `status`: The new status.
+        #[weight = T::WeightInfo::set_exchange_status()]
+        #[transactional]
+        pub fn set_exchange_status(origin,
+            swap_handler: SwapHandlerOf<T>,
+            status: ExchangeStatus,
+        ) -> dispatch::DispatchResult
+        {
+            T::ControlOrigin::ensure_origin(origin)?;
+
+            let exchange_id = Self::get_exchange_id(&swap_handler)?;
+            ensure!(
+                <Exchanges<T>>::contains_key(exchange_id) || <Pairs<T>>::contains_key(exchange_id),
+                Error::<T>::ExchangeNotExists
+            );
+
+            <ExchangeStatuses<T>>::insert(exchange_id, status);
+
+            Self::deposit_event(RawEvent::ExchangeStatusChanged(exchange_id, status));
+
+            Ok(())
+        }
+
+        /// Set the status of all exchanges and pairs.
+        ///
+        /// The dispatch origin for this call must be `ControlOrigin`.
+        ///
+        /// - `status`: The new global status.
+        #[weight = T::WeightInfo::set_global_status()]
+        #[transactional]
+        pub fn set_global_status(origin,
+            status: ExchangeStatus,
+        ) -> dispatch::DispatchResult
+        {
+            T::ControlOrigin::ensure_origin(origin)?;
+
+            GlobalStatus::put(status);
+
+            Self::deposit_event(RawEvent::GlobalStatusChanged(status));
+
+            Ok(())
+        }
+
+        /// Set the account receiving the protocol fee. The protocol fee, 1/6th of the growth
+        /// in sqrt(k), is minted in liquidity token to the account on every liquidity event.
+        ///
+        /// The dispatch origin for this call must be `ControlOrigin`.
+        ///
+        /// - `fee_to`: The protocol fee receiver, `None` to turn off the protocol fee.
+        #[weight = T::WeightInfo::set_fee_to()]
+        #[transactional]
+        pub fn set_fee_to(origin,
+            fee_to: Option<T::AccountId>,
+        ) -> dispatch::DispatchResult
+        {
+            T::ControlOrigin::ensure_origin(origin)?;
+
+            <FeeTo<T>>::set(fee_to.clone());
+
+            Self::deposit_event(RawEvent::FeeToChanged(fee_to));
 
             Ok(())
         }
@@ -224,7 +653,8 @@ decl_module! {
         /// - `min_liquidity`: Min amount of exchange shares(ZLK) to create.
         /// - `max_token`: Max amount of token to input.
         /// - `deadline`: When to invalidate the transaction.
-        #[weight = 0]
+        #[weight = T::WeightInfo::add_liquidity()]
+        #[transactional]
         pub fn add_liquidity(origin,
             swap_handler: SwapHandlerOf<T>,
             currency_amount: BalanceOf<T>,
@@ -243,45 +673,59 @@ decl_module! {
             ensure!(currency_amount > Zero::zero(), Error::<T>::ZeroCurrency);
 
             let exchange_id = Self::get_exchange_id(&swap_handler)?;
+            Self::ensure_add_liquidity_allowed(exchange_id)?;
 
-            if let Some(exchange) = Self::get_exchange(exchange_id) {
-                let total_liquidity = <zenlink_assets::Module<T>>::total_supply(&exchange.liquidity_id);
-
-                if total_liquidity > Zero::zero() {
-                    ensure!(min_liquidity > Zero::zero(), Error::<T>::RequestedZeroLiquidity);
-                    let currency_reserve = Self::convert(Self::get_currency_reserve(&exchange));
-                    let token_reserve = Self::get_token_reserve(&exchange);
-                    let token_amount = Self::convert(currency_amount) * token_reserve / currency_reserve;
-                    let liquidity_minted = Self::convert(currency_amount) * total_liquidity / currency_reserve;
+            Self::do_add_liquidity(&who, exchange_id, currency_amount, min_liquidity, max_token)?;
 
-                    ensure!(max_token >= token_amount, Error::<T>::TooManyToken);
-                    ensure!(liquidity_minted >= min_liquidity, Error::<T>::TooLowLiquidity);
-                    ensure!(<zenlink_assets::Module<T>>::allowances(&exchange.token_id, &who, &exchange.account) >= token_amount, Error::<T>::AllowanceLow);
+            Ok(())
+        }
 
-                    T::Currency::transfer(&who, &exchange.account, currency_amount, ExistenceRequirement::KeepAlive)?;
-                    <zenlink_assets::Module<T>>::inner_mint(&exchange.liquidity_id, &who, liquidity_minted)?;
-                    <zenlink_assets::Module<T>>::inner_transfer_from(&exchange.token_id, &who, &exchange.account, &exchange.account, token_amount)?;
+        /// Injecting liquidity to specific exchange liquidity pool by the desired amounts of both
+        /// currency and token. Only the amounts in proportion to the reserves are deposited, which
+        /// must be no less than the minimum amounts in case the price moved since the quote.
+        /// The fresh exchange takes the desired amounts.
+        ///
+        /// - `swap_handler`: The wrapper of exchangeId and assetId to access.
+        /// - `currency_desired`: Max amount of currency to deposit.
+        /// - `token_desired`: Max amount of token to deposit.
+        /// - `currency_min`: Min amount of currency to deposit.
+        /// - `token_min`: Min amount of token to deposit.
+        /// - `deadline`: When to invalidate the transaction.
+        #[weight = T::WeightInfo::add_liquidity_by_amounts()]
+        #[transactional]
+        pub fn add_liquidity_by_amounts(origin,
+            swap_handler: SwapHandlerOf<T>,
+            currency_desired: BalanceOf<T>,
+            token_desired: TokenBalance<T>,
+            currency_min: BalanceOf<T>,
+            token_min: TokenBalance<T>,
+            deadline: T::BlockNumber,
+        ) -> dispatch::DispatchResult
+        {
+            let now = frame_system::Module::<T>::block_number();
+            ensure!(deadline > now, Error::<T>::Deadline);
 
-                    Self::deposit_event(RawEvent::LiquidityAdded(exchange_id, who, currency_amount, token_amount));
-                } else {
-                    // Fresh exchange with no liquidity
-                    let token_amount = max_token;
-                    ensure!(<zenlink_assets::Module<T>>::allowances(&exchange.token_id, &who, &exchange.account) >= token_amount, Error::<T>::AllowanceLow);
+            let who = ensure_signed(origin)?;
 
-                    T::Currency::transfer(&who, &exchange.account, currency_amount, ExistenceRequirement::KeepAlive)?;
+            ensure!(token_desired > Zero::zero(), Error::<T>::ZeroToken);
+            ensure!(currency_desired > Zero::zero(), Error::<T>::ZeroCurrency);
 
-                    let initial_liquidity: u64 = T::Currency::free_balance(&exchange.account).saturated_into::<u64>();
+            let exchange_id = Self::get_exchange_id(&swap_handler)?;
+            Self::ensure_add_liquidity_allowed(exchange_id)?;
 
-                    <zenlink_assets::Module<T>>::inner_mint(&exchange.liquidity_id, &who, initial_liquidity.saturated_into())?;
-                    <zenlink_assets::Module<T>>::inner_transfer_from(&exchange.token_id, &who, &exchange.account, &exchange.account, token_amount)?;
+            let exchange = Self::get_exchange(exchange_id).ok_or(Error::<T>::ExchangeNotExists)?;
+            let (currency_amount, token_amount) = Self::get_liquidity_amounts(
+                &exchange,
+                currency_desired,
+                token_desired,
+                currency_min,
+                token_min,
+            )?;
 
-                    Self::deposit_event(RawEvent::LiquidityAdded(exchange_id, who, currency_amount, token_amount));
-                }
+            // Any liquidity is enough, the amounts deposited are bounded instead.
+            Self::do_add_liquidity(&who, exchange_id, currency_amount, One::one(), token_amount)?;
 
-                Ok(())
-            } else {
-                Err(Error::<T>::ExchangeNotExists.into())
-            }
+            Ok(())
         }
 
         /// Remove liquidity from specific exchange liquidity pool in the form of burning
@@ -293,7 +737,8 @@ decl_module! {
         /// - `min_currency`: Minimum currency to withdraw.
         /// - `min_token`: Minimum token to withdraw.
         /// - `deadline`: When to invalidate the transaction.
-        #[weight = 0]
+        #[weight = T::WeightInfo::remove_liquidity()]
+        #[transactional]
         pub fn remove_liquidity(origin,
             swap_handler: SwapHandlerOf<T>,
             zlk_to_burn: TokenBalance<T>,
@@ -311,24 +756,34 @@ decl_module! {
 
             let exchange_id = Self::get_exchange_id(&swap_handler)?;
 
-            if let Some(exchange) = Self::get_exchange(exchange_id) {
-                let total_liquidity = <zenlink_assets::Module<T>>::total_supply(&exchange.liquidity_id);
+            if let Some(mut exchange) = Self::get_exchange(exchange_id) {
+                let protocol_fee = Self::calculate_protocol_fee(exchange_id, exchange.liquidity_id, Self::get_k(&exchange)?)?;
+                let total_liquidity = Self::total_liquidity_with_fee(exchange.liquidity_id, &protocol_fee)?;
 
                 ensure!(total_liquidity > Zero::zero(), Error::<T>::NoLiquidity);
 
-                let token_reserve = Self::get_token_reserve(&exchange);
-                let currency_reserve = Self::get_currency_reserve(&exchange);
-                let currency_amount = zlk_to_burn * Self::convert(currency_reserve) / total_liquidity;
-                let token_amount = zlk_to_burn * token_reserve / total_liquidity;
+                let token_reserve = Self::to_u256(Self::get_token_reserve(&exchange))?;
+                let currency_reserve = Self::to_u256(Self::get_currency_reserve(&exchange))?;
+                let zlk_to_burn_u256 = Self::to_u256(zlk_to_burn)?;
+                let total_liquidity = Self::to_u256(total_liquidity)?;
+                let currency_amount: BalanceOf<T> = Self::from_u256(Self::mul_div(zlk_to_burn_u256, currency_reserve, total_liquidity)?)?;
+                let token_amount: TokenBalance<T> = Self::from_u256(Self::mul_div(zlk_to_burn_u256, token_reserve, total_liquidity)?)?;
 
-                ensure!(Self::unconvert(currency_amount) >= min_currency, Error::<T>::NotEnoughCurrency);
+                ensure!(currency_amount >= min_currency, Error::<T>::NotEnoughCurrency);
                 ensure!(token_amount >= min_token, Error::<T>::NotEnoughToken);
 
+                Self::mint_protocol_fee(exchange_id, exchange.liquidity_id, &protocol_fee)?;
                 <zenlink_assets::Module<T>>::inner_burn(&exchange.liquidity_id, &who, zlk_to_burn)?;
-                T::Currency::transfer(&exchange.account, &who, Self::unconvert(currency_amount), ExistenceRequirement::AllowDeath)?;
+                T::Currency::transfer(&exchange.account, &who, currency_amount, ExistenceRequirement::AllowDeath)?;
                 <zenlink_assets::Module<T>>::inner_transfer(&exchange.token_id, &exchange.account, &who, token_amount)?;
 
-                Self::deposit_event(RawEvent::LiquidityRemoved(exchange_id, who, Self::unconvert(currency_amount), token_amount));
+                exchange.currency_reserve = Self::sub_reserve(exchange.currency_reserve, currency_amount)?;
+                exchange.token_reserve = Self::sub_reserve(exchange.token_reserve, token_amount)?;
+
+                Self::update_k_last(exchange_id, Self::get_k(&exchange)?, &protocol_fee);
+                Self::put_exchange(exchange_id, exchange)?;
+
+                Self::deposit_event(RawEvent::LiquidityRemoved(exchange_id, who, currency_amount, token_amount));
 
                 Ok(())
             } else {
@@ -336,6 +791,81 @@ decl_module! {
             }
         }
 
+        /// Injecting liquidity to specific exchange liquidity pool with only the native currency
+        /// or only the token. The optimal part of it is swapped to the other asset in the exchange,
+        /// then the rest is deposited with the bought asset as liquidity.
+        ///
+        /// - `swap_handler`: The wrapper of exchangeId and assetId to access.
+        /// - `asset_in`: The asset to deposit, native currency or the token of the exchange.
+        /// - `amount_in`: Amount of the asset to deposit.
+        /// - `min_liquidity`: Min amount of exchange shares(ZLK) to create.
+        /// - `deadline`: When to invalidate the transaction.
+        #[weight = T::WeightInfo::add_liquidity_single_sided()]
+        #[transactional]
+        pub fn add_liquidity_single_sided(origin,
+            swap_handler: SwapHandlerOf<T>,
+            asset_in: SwapAssetOf<T>,
+            amount_in: AssetBalance,
+            min_liquidity: TokenBalance<T>,
+            deadline: T::BlockNumber,
+        ) -> dispatch::DispatchResult
+        {
+            let now = frame_system::Module::<T>::block_number();
+            ensure!(deadline > now, Error::<T>::Deadline);
+
+            let who = ensure_signed(origin)?;
+
+            ensure!(amount_in > Zero::zero(), Self::zero_amount_error(asset_in));
+            ensure!(min_liquidity > Zero::zero(), Error::<T>::RequestedZeroLiquidity);
+
+            let exchange_id = Self::get_exchange_id(&swap_handler)?;
+            Self::ensure_add_liquidity_allowed(exchange_id)?;
+            Self::ensure_swap_allowed(exchange_id)?;
+
+            let liquidity_minted = Self::do_add_liquidity_single_sided(&who, exchange_id, asset_in, amount_in, min_liquidity)?;
+
+            Self::deposit_event(RawEvent::LiquidityAddedSingleSided(exchange_id, who, asset_in, amount_in, liquidity_minted));
+
+            Ok(())
+        }
+
+        /// Remove liquidity from specific exchange liquidity pool in the form of burning
+        /// shares(ZLK), and withdrawing everything in a single asset. The other asset withdrawn
+        /// is swapped to it in the exchange.
+        ///
+        /// - `swap_handler`: The wrapper of exchangeId and assetId to access.
+        /// - `zlk_to_burn`: Liquidity amount to remove.
+        /// - `asset_out`: The asset to withdraw, native currency or the token of the exchange.
+        /// - `min_amount_out`: Minimum amount of the asset to withdraw.
+        /// - `deadline`: When to invalidate the transaction.
+        #[weight = T::WeightInfo::remove_liquidity_single_sided()]
+        #[transactional]
+        pub fn remove_liquidity_single_sided(origin,
+            swap_handler: SwapHandlerOf<T>,
+            zlk_to_burn: TokenBalance<T>,
+            asset_out: SwapAssetOf<T>,
+            min_amount_out: AssetBalance,
+            deadline: T::BlockNumber,
+        ) -> dispatch::DispatchResult
+        {
+            let now = frame_system::Module::<T>::block_number();
+            ensure!(deadline > now, Error::<T>::Deadline);
+
+            let who = ensure_signed(origin)?;
+
+            ensure!(zlk_to_burn > Zero::zero(), Error::<T>::BurnZeroZLKShares);
+            ensure!(min_amount_out > Zero::zero(), Self::zero_amount_error(asset_out));
+
+            let exchange_id = Self::get_exchange_id(&swap_handler)?;
+            Self::ensure_swap_allowed(exchange_id)?;
+
+            let amount_out = Self::do_remove_liquidity_single_sided(&who, exchange_id, zlk_to_burn, asset_out, min_amount_out)?;
+
+            Self::deposit_event(RawEvent::LiquidityRemovedSingleSided(exchange_id, who, asset_out, zlk_to_burn, amount_out));
+
+            Ok(())
+        }
+
         /// Swap currency to token.
         ///
         /// User specifies the exact amount of currency to sold and the amount not less the minimum
@@ -345,7 +875,8 @@ decl_module! {
         /// - `min_token`: The minimum token expected to buy.
         /// - `deadline`: When to invalidate the transaction.
         /// - `recipient`: Receiver of the bought token.
-        #[weight = 0]
+        #[weight = T::WeightInfo::currency_to_token_input()]
+        #[transactional]
         pub fn currency_to_token_input(origin,
             swap_handler: SwapHandlerOf<T>,
             currency_sold: BalanceOf<T>,
@@ -363,17 +894,20 @@ decl_module! {
             ensure!(min_token > Zero::zero(), Error::<T>::ZeroToken);
 
             let exchange_id = Self::get_exchange_id(&swap_handler)?;
+            Self::ensure_swap_allowed(exchange_id)?;
 
-            if let Some(exchange) = Self::get_exchange(exchange_id) {
-                let token_reserve = Self::get_token_reserve(&exchange);
-                let currency_reserve = Self::get_currency_reserve(&exchange);
-                let tokens_bought = Self::get_input_price(Self::convert(currency_sold), Self::convert(currency_reserve), token_reserve);
+            if let Some(mut exchange) = Self::get_exchange(exchange_id) {
+                let tokens_bought = Self::get_currency_to_token_input_price(&exchange, currency_sold)?;
 
                 ensure!(tokens_bought >= min_token, Error::<T>::NotEnoughToken);
 
                 T::Currency::transfer(&buyer, &exchange.account, currency_sold, ExistenceRequirement::KeepAlive)?;
                 <zenlink_assets::Module<T>>::inner_transfer(&exchange.token_id, &exchange.account, &recipient, tokens_bought)?;
 
+                exchange.currency_reserve = Self::add_reserve(exchange.currency_reserve, currency_sold)?;
+                exchange.token_reserve = Self::sub_reserve(exchange.token_reserve, tokens_bought)?;
+                Self::put_exchange(exchange_id, exchange)?;
+
                 Self::deposit_event(RawEvent::TokenPurchase(exchange_id, buyer, currency_sold, tokens_bought, recipient));
 
                 Ok(())
@@ -391,7 +925,8 @@ decl_module! {
         /// - `max_currency`: The maximum currency expected to be sold.
         /// - `deadline`: When to invalidate the transaction.
         /// - `recipient`: Receiver of the bought token.
-        #[weight = 0]
+        #[weight = T::WeightInfo::currency_to_token_output()]
+        #[transactional]
         pub fn currency_to_token_output(origin,
             swap_handler: SwapHandlerOf<T>,
             tokens_bought: TokenBalance<T>,
@@ -409,18 +944,21 @@ decl_module! {
             ensure!(max_currency > Zero::zero(), Error::<T>::ZeroCurrency);
 
             let exchange_id = Self::get_exchange_id(&swap_handler)?;
+            Self::ensure_swap_allowed(exchange_id)?;
 
-            if let Some(exchange) = Self::get_exchange(exchange_id) {
-                let token_reserve = Self::get_token_reserve(&exchange);
-                let currency_reserve = Self::get_currency_reserve(&exchange);
-                let currency_sold = Self::get_output_price(tokens_bought, Self::convert(currency_reserve), token_reserve);
+            if let Some(mut exchange) = Self::get_exchange(exchange_id) {
+                let currency_sold = Self::get_currency_to_token_output_price(&exchange, tokens_bought)?;
 
-                ensure!(Self::unconvert(currency_sold) <= max_currency, Error::<T>::TooExpensiveCurrency);
+                ensure!(currency_sold <= max_currency, Error::<T>::TooExpensiveCurrency);
 
-                T::Currency::transfer(&buyer, &exchange.account, Self::unconvert(currency_sold), ExistenceRequirement::KeepAlive)?;
+                T::Currency::transfer(&buyer, &exchange.account, currency_sold, ExistenceRequirement::KeepAlive)?;
                 <zenlink_assets::Module<T>>::inner_transfer(&exchange.token_id, &exchange.account, &recipient, tokens_bought)?;
 
-                Self::deposit_event(RawEvent::TokenPurchase(exchange_id, buyer, Self::unconvert(currency_sold), tokens_bought, recipient));
+                exchange.currency_reserve = Self::add_reserve(exchange.currency_reserve, currency_sold)?;
+                exchange.token_reserve = Self::sub_reserve(exchange.token_reserve, tokens_bought)?;
+                Self::put_exchange(exchange_id, exchange)?;
+
+                Self::deposit_event(RawEvent::TokenPurchase(exchange_id, buyer, currency_sold, tokens_bought, recipient));
 
                 Ok(())
             } else {
@@ -437,7 +975,8 @@ decl_module! {
         /// - `min_currency`: The minimum currency expected to buy.
         /// - `deadline`: When to invalidate the transaction.
         /// - `recipient`: Receiver of the bought currency.
-        #[weight = 0]
+        #[weight = T::WeightInfo::token_to_currency_input()]
+        #[transactional]
         pub fn token_to_currency_input(origin,
             swap_handler: SwapHandlerOf<T>,
             token_sold: TokenBalance<T>,
@@ -455,19 +994,21 @@ decl_module! {
             ensure!(min_currency > Zero::zero(), Error::<T>::ZeroCurrency);
 
             let exchange_id = Self::get_exchange_id(&swap_handler)?;
+            Self::ensure_swap_allowed(exchange_id)?;
 
-            if let Some(exchange) = Self::get_exchange(exchange_id) {
-                let token_reserve = Self::get_token_reserve(&exchange);
-                let currency_reserve = Self::get_currency_reserve(&exchange);
-                let currency_bought = Self::get_input_price(token_sold, token_reserve, Self::convert(currency_reserve));
+            if let Some(mut exchange) = Self::get_exchange(exchange_id) {
+                let currency_bought = Self::get_token_to_currency_input_price(&exchange, token_sold)?;
+
+                ensure!(currency_bought >= min_currency, Error::<T>::NotEnoughCurrency);
 
-                ensure!(currency_bought >= Self::convert(min_currency), Error::<T>::NotEnoughCurrency);
-                ensure!(<zenlink_assets::Module<T>>::allowances(&exchange.token_id, &buyer, &exchange.account) >= token_sold, Error::<T>::AllowanceLow);
+                T::Currency::transfer(&exchange.account, &recipient, currency_bought, ExistenceRequirement::AllowDeath)?;
+                <zenlink_assets::Module<T>>::inner_transfer(&exchange.token_id, &buyer, &exchange.account, token_sold)?;
 
-                T::Currency::transfer(&exchange.account, &recipient, Self::unconvert(currency_bought), ExistenceRequirement::AllowDeath)?;
-                <zenlink_assets::Module<T>>::inner_transfer_from(&exchange.token_id, &buyer, &exchange.account, &exchange.account, token_sold)?;
+                exchange.currency_reserve = Self::sub_reserve(exchange.currency_reserve, currency_bought)?;
+                exchange.token_reserve = Self::add_reserve(exchange.token_reserve, token_sold)?;
+                Self::put_exchange(exchange_id, exchange)?;
 
-                Self::deposit_event(RawEvent::CurrencyPurchase(exchange_id, buyer, Self::unconvert(currency_bought), token_sold, recipient));
+                Self::deposit_event(RawEvent::CurrencyPurchase(exchange_id, buyer, currency_bought, token_sold, recipient));
 
                 Ok(())
             } else {
@@ -484,7 +1025,8 @@ decl_module! {
         /// - `max_token`: The maximum currency expected to be sold.
         /// - `deadline`: When to invalidate the transaction.
         /// - `recipient`: Receiver of the bought currency.
-        #[weight = 0]
+        #[weight = T::WeightInfo::token_to_currency_output()]
+        #[transactional]
         pub fn token_to_currency_output(origin,
             swap_handler: SwapHandlerOf<T>,
             currency_bought: BalanceOf<T>,
@@ -502,17 +1044,19 @@ decl_module! {
             ensure!(currency_bought > Zero::zero(), Error::<T>::ZeroCurrency);
 
             let exchange_id = Self::get_exchange_id(&swap_handler)?;
+            Self::ensure_swap_allowed(exchange_id)?;
 
-            if let Some(exchange) = Self::get_exchange(exchange_id) {
-                let token_reserve = Self::get_token_reserve(&exchange);
-                let currency_reserve = Self::get_currency_reserve(&exchange);
-                let token_sold = Self::get_output_price(Self::convert(currency_bought), token_reserve, Self::convert(currency_reserve));
+            if let Some(mut exchange) = Self::get_exchange(exchange_id) {
+                let token_sold = Self::get_token_to_currency_output_price(&exchange, currency_bought)?;
 
                 ensure!(max_token >= token_sold, Error::<T>::TooExpensiveToken);
-                ensure!(<zenlink_assets::Module<T>>::allowances(&exchange.token_id, &buyer, &exchange.account) >= token_sold, Error::<T>::AllowanceLow);
 
                 T::Currency::transfer(&exchange.account, &buyer, currency_bought, ExistenceRequirement::AllowDeath)?;
-                <zenlink_assets::Module<T>>::inner_transfer_from(&exchange.token_id, &buyer, &exchange.account, &exchange.account, token_sold)?;
+                <zenlink_assets::Module<T>>::inner_transfer(&exchange.token_id, &buyer, &exchange.account, token_sold)?;
+
+                exchange.currency_reserve = Self::sub_reserve(exchange.currency_reserve, currency_bought)?;
+                exchange.token_reserve = Self::add_reserve(exchange.token_reserve, token_sold)?;
+                Self::put_exchange(exchange_id, exchange)?;
 
                 Self::deposit_event(RawEvent::CurrencyPurchase(exchange_id, buyer, currency_bought, token_sold, recipient));
 
@@ -532,7 +1076,8 @@ decl_module! {
         /// - `min_other_token`: The minimum other token expected to buy.
         /// - `deadline`: When to invalidate the transaction.
         /// - `recipient`: Receiver of the bought other token.
-        #[weight = 0]
+        #[weight = T::WeightInfo::token_to_token_input()]
+        #[transactional]
         pub fn token_to_token_input(origin,
             swap_handler: SwapHandlerOf<T>,
             other_swap_handle: SwapHandlerOf<T>,
@@ -552,29 +1097,34 @@ decl_module! {
 
             let exchange_id = Self::get_exchange_id(&swap_handler)?;
             let other_exchange_id = Self::get_exchange_id(&other_swap_handle)?;
+            Self::ensure_swap_allowed(exchange_id)?;
+            Self::ensure_swap_allowed(other_exchange_id)?;
+            // The reserves of the repeated exchange would be outdated.
+            ensure!(exchange_id != other_exchange_id, Error::<T>::InvalidPath);
             let get_exchange = Self::get_exchange(exchange_id);
             let get_othere_exchange = Self::get_exchange(other_exchange_id);
             if get_exchange.is_none() || get_othere_exchange.is_none() {
                 return Err(Error::<T>::ExchangeNotExists.into())
             }
-            let exchange = get_exchange.unwrap();
-            let other_exchange = get_othere_exchange.unwrap();
-
-            let token_reserve = Self::get_token_reserve(&exchange);
-            let currency_reserve = Self::get_currency_reserve(&exchange);
-            let currency_bought = Self::get_input_price(token_sold, token_reserve, Self::convert(currency_reserve));
+            let mut exchange = get_exchange.unwrap();
+            let mut other_exchange = get_othere_exchange.unwrap();
 
-            let other_token_reserve = Self::get_token_reserve(&other_exchange);
-            let other_currency_reserve = Self::get_currency_reserve(&other_exchange);
-            let other_token_bought = Self::get_input_price(currency_bought, Self::convert(other_currency_reserve), other_token_reserve);
+            let currency_bought = Self::get_token_to_currency_input_price(&exchange, token_sold)?;
+            let other_token_bought = Self::get_currency_to_token_input_price(&other_exchange, currency_bought)?;
 
             ensure!(other_token_bought >= min_other_token, Error::<T>::NotEnoughToken);
-            ensure!(<zenlink_assets::Module<T>>::allowances(&exchange.token_id, &buyer, &exchange.account) >= token_sold, Error::<T>::AllowanceLow);
 
-            <zenlink_assets::Module<T>>::inner_transfer_from(&exchange.token_id, &buyer, &exchange.account, &exchange.account, token_sold)?;
-            T::Currency::transfer(&exchange.account, &other_exchange.account, Self::unconvert(currency_bought), ExistenceRequirement::KeepAlive)?;
+            <zenlink_assets::Module<T>>::inner_transfer(&exchange.token_id, &buyer, &exchange.account, token_sold)?;
+            T::Currency::transfer(&exchange.account, &other_exchange.account, currency_bought, ExistenceRequirement::KeepAlive)?;
             <zenlink_assets::Module<T>>::inner_transfer(&other_exchange.token_id, &other_exchange.account, &recipient, other_token_bought)?;
 
+            exchange.token_reserve = Self::add_reserve(exchange.token_reserve, token_sold)?;
+            exchange.currency_reserve = Self::sub_reserve(exchange.currency_reserve, currency_bought)?;
+            other_exchange.currency_reserve = Self::add_reserve(other_exchange.currency_reserve, currency_bought)?;
+            other_exchange.token_reserve = Self::sub_reserve(other_exchange.token_reserve, other_token_bought)?;
+            Self::put_exchange(exchange_id, exchange)?;
+            Self::put_exchange(other_exchange_id, other_exchange)?;
+
             Self::deposit_event(RawEvent::OtherTokenPurchase(exchange_id, other_exchange_id, buyer, token_sold, other_token_bought, recipient));
 
             Ok(())
@@ -590,7 +1140,8 @@ decl_module! {
         /// - `max_token`: The maximum token expected to be sold.
         /// - `deadline`: When to invalidate the transaction.
         /// - `recipient`: Receiver of the bought currency.
-        #[weight = 0]
+        #[weight = T::WeightInfo::token_to_token_output()]
+        #[transactional]
         pub fn token_to_token_output(origin,
             swap_handler: SwapHandlerOf<T>,
             other_swap_handle: SwapHandlerOf<T>,
@@ -609,169 +1160,1776 @@ decl_module! {
 
             let exchange_id = Self::get_exchange_id(&swap_handler)?;
             let other_exchange_id = Self::get_exchange_id(&other_swap_handle)?;
+            Self::ensure_swap_allowed(exchange_id)?;
+            Self::ensure_swap_allowed(other_exchange_id)?;
+            // The reserves of the repeated exchange would be outdated.
+            ensure!(exchange_id != other_exchange_id, Error::<T>::InvalidPath);
             let get_exchange = Self::get_exchange(exchange_id);
             let get_othere_exchange = Self::get_exchange(other_exchange_id);
             if get_exchange.is_none() || get_othere_exchange.is_none() {
                 return Err(Error::<T>::ExchangeNotExists.into())
             }
-            let exchange = get_exchange.unwrap();
-            let other_exchange = get_othere_exchange.unwrap();
-
-            let other_token_reserve = Self::get_token_reserve(&other_exchange);
-            let other_currency_reserve = Self::get_currency_reserve(&other_exchange);
-            let currency_sold = Self::get_output_price(other_token_bought, Self::convert(other_currency_reserve), other_token_reserve);
+            let mut exchange = get_exchange.unwrap();
+            let mut other_exchange = get_othere_exchange.unwrap();
 
-            let token_reserve = Self::get_token_reserve(&exchange);
-            let currency_reserve = Self::get_currency_reserve(&exchange);
-            let token_sold = Self::get_output_price(currency_sold, token_reserve, Self::convert(currency_reserve));
+            let currency_sold = Self::get_currency_to_token_output_price(&other_exchange, other_token_bought)?;
+            let token_sold = Self::get_token_to_currency_output_price(&exchange, currency_sold)?;
 
             ensure!(max_token >= token_sold, Error::<T>::TooExpensiveToken);
-            ensure!(<zenlink_assets::Module<T>>::allowances(&exchange.token_id, &buyer, &exchange.account) >= token_sold, Error::<T>::AllowanceLow);
 
-            <zenlink_assets::Module<T>>::inner_transfer_from(&exchange.token_id, &buyer, &exchange.account, &exchange.account, token_sold)?;
-            T::Currency::transfer(&exchange.account, &other_exchange.account, Self::unconvert(currency_sold), ExistenceRequirement::KeepAlive)?;
+            <zenlink_assets::Module<T>>::inner_transfer(&exchange.token_id, &buyer, &exchange.account, token_sold)?;
+            T::Currency::transfer(&exchange.account, &other_exchange.account, currency_sold, ExistenceRequirement::KeepAlive)?;
             <zenlink_assets::Module<T>>::inner_transfer(&other_exchange.token_id, &other_exchange.account, &recipient, other_token_bought)?;
 
+            exchange.token_reserve = Self::add_reserve(exchange.token_reserve, token_sold)?;
+            exchange.currency_reserve = Self::sub_reserve(exchange.currency_reserve, currency_sold)?;
+            other_exchange.currency_reserve = Self::add_reserve(other_exchange.currency_reserve, currency_sold)?;
+            other_exchange.token_reserve = Self::sub_reserve(other_exchange.token_reserve, other_token_bought)?;
+            Self::put_exchange(exchange_id, exchange)?;
+            Self::put_exchange(other_exchange_id, other_exchange)?;
+
             Self::deposit_event(RawEvent::OtherTokenPurchase(exchange_id, other_exchange_id, buyer, token_sold, other_token_bought, recipient));
 
             Ok(())
         }
-    }
-}
-
-impl<T: Trait> Module<T> {
-    /// Get the exchange_id by unwrapping the swap_handler.
-    /// Return exist exchange_id or `ExchangeNotExists` error.
-    pub fn get_exchange_id(swap_handler: &SwapHandlerOf<T>) -> Result<T::ExchangeId, Error<T>> {
-        match swap_handler {
-            SwapHandler::ExchangeId(exchange_id) => Ok(*exchange_id),
-            SwapHandler::AssetId(asset_id) => {
-                Self::token_to_exchange(asset_id).ok_or(Error::<T>::ExchangeNotExists)
-            }
-        }
-    }
 
-    pub fn get_exchange_info(id :T::ExchangeId) -> Option<Exchange<T::AccountId, T::AssetId>> {
-        Self::get_exchange(id)
-    }
-    /// Swap Currency to Token.
-    /// Return Amount of Token bought.
-    pub fn get_currency_to_token_input_price(
-        exchange: &Exchange<T::AccountId, T::AssetId>,
-        currency_sold: BalanceOf<T>,
-    ) -> TokenBalance<T> {
-        if currency_sold == Zero::zero() {
-            return Zero::zero();
-        }
+        /// Create a pair with two tokens which would swap with each other directly
+        ///
+        /// - `token_a`: The exist asset's id.
+        /// - `token_b`: The other exist asset's id.
+        #[weight = T::WeightInfo::create_pair()]
+        #[transactional]
+        pub fn create_pair(origin,
+            token_a: T::AssetId,
+            token_b: T::AssetId,
+        ) -> dispatch::DispatchResult
+        {
+            ensure_signed(origin)?;
 
-        let token_reserve = Self::get_token_reserve(exchange);
-        let currency_reserve = Self::get_currency_reserve(exchange);
-        Self::get_input_price(
-            Self::convert(currency_sold),
-            Self::convert(currency_reserve),
-            token_reserve,
-        )
-    }
+            ensure!(token_a != token_b, Error::<T>::IdenticalTokens);
+            ensure!(<zenlink_assets::Module<T>>::asset_info(&token_a).is_some(), Error::<T>::TokenNotExists);
+            ensure!(<zenlink_assets::Module<T>>::asset_info(&token_b).is_some(), Error::<T>::TokenNotExists);
+            ensure!(Self::zlk_to_exchange(token_a).is_none(), Error::<T>::DeniedSwap);
+            ensure!(Self::zlk_to_exchange(token_b).is_none(), Error::<T>::DeniedSwap);
 
-    /// Swap Currency to Token.
-    /// Return Amount of Currency sold.
-    pub fn get_currency_to_token_output_price(
-        exchange: &Exchange<T::AccountId, T::AssetId>,
-        tokens_bought: TokenBalance<T>,
-    ) -> TokenBalance<T> {
-        if tokens_bought == Zero::zero() {
-            return Zero::zero();
-        }
+            let (token_0, token_1) = Self::sort_tokens(token_a, token_b);
+            ensure!(Self::tokens_to_pair((token_0, token_1)).is_none(), Error::<T>::PairAlreadyExists);
 
-        let token_reserve = Self::get_token_reserve(exchange);
-        let currency_reserve = Self::get_currency_reserve(exchange);
-        Self::get_output_price(
-            tokens_bought,
-            Self::convert(currency_reserve),
-            token_reserve,
-        )
-    }
+            let pair_id = Self::next_exchange_id();
+            let next_id = pair_id.checked_add(&One::one())
+                .ok_or("Overflow")?;
 
-    /// Swap Token to Currency.
-    /// Return Amount of Currency bought.
-    pub fn get_token_to_currency_input_price(
-        exchange: &Exchange<T::AccountId, T::AssetId>,
-        token_sold: TokenBalance<T>,
-    ) -> TokenBalance<T> {
-        if token_sold == Zero::zero() {
-            return Zero::zero();
-        }
+            let account: T::AccountId = T::ModuleId::get().into_sub_account(pair_id);
 
-        let token_reserve = Self::get_token_reserve(exchange);
-        let currency_reserve = Self::get_currency_reserve(exchange);
-        Self::get_input_price(token_sold, token_reserve, Self::convert(currency_reserve))
-    }
+            // create a new lp token for pair
+            let liquidity_id = <zenlink_assets::Module<T>>::inner_issue(&account, Zero::zero(), ZLK);
+            let new_pair = Pair {
+                token_0,
+                token_1,
+                liquidity_id,
+                account: account.clone(),
+                fee_rate: None,
+                reserve_0: Zero::zero(),
+                reserve_1: Zero::zero(),
+            };
 
-    /// Swap Token to Currency.
-    /// Return Amount of Token bought.
-    pub fn get_token_to_currency_output_price(
-        exchange: &Exchange<T::AccountId, T::AssetId>,
-        currency_bought: BalanceOf<T>,
-    ) -> TokenBalance<T> {
-        if currency_bought == Zero::zero() {
-            return Zero::zero();
-        }
+            <TokensToPair<T>>::insert((token_0, token_1), pair_id);
+            <ZLKToExchange<T>>::insert(liquidity_id, pair_id);
+            <Pairs<T>>::insert(pair_id, new_pair);
+            <NextExchangeId<T>>::put(next_id);
 
-        let token_reserve = Self::get_token_reserve(exchange);
-        let currency_reserve = Self::get_currency_reserve(exchange);
-        Self::get_output_price(
-            Self::convert(currency_bought),
-            token_reserve,
-            Self::convert(currency_reserve),
-        )
-    }
+            Self::deposit_event(RawEvent::PairCreated(pair_id, account, token_0, token_1));
 
-    /// Pricing function for converting between Currency and Token.
-    /// Return Amount of Currency or Token bought.
-    fn get_input_price(
-        input_amount: TokenBalance<T>,
-        input_reserve: TokenBalance<T>,
-        output_reserve: TokenBalance<T>,
-    ) -> TokenBalance<T> {
-        let input_amount_with_fee = input_amount * 997.into();
-        let numerator = input_amount_with_fee * output_reserve;
-        let denominator = (input_reserve * 1000.into()) + input_amount_with_fee;
-        numerator / denominator
-    }
+            Ok(())
+        }
 
-    /// Pricing function for converting between Currency and Token.
-    /// Return Amount of Currency or Token sold.
+        /// Injecting liquidity to specific pair liquidity pool in the form of depositing
+        /// both tokens to the pair account and issue liquidity pool token in proportion
+        /// to the caller who is the liquidity provider.
+        ///
+        /// - `token_a`: The asset id of the token of the pair.
+        /// - `token_b`: The asset id of the other token of the pair.
+        /// - `amount_a`: Amount of token_a to lock.
+        /// - `min_liquidity`: Min amount of pair shares(ZLK) to create.
+        /// - `max_amount_b`: Max amount of token_b to input.
+        /// - `deadline`: When to invalidate the transaction.
+        #[weight = T::WeightInfo::add_pair_liquidity()]
+        #[transactional]
+        pub fn add_pair_liquidity(origin,
+            token_a: T::AssetId,
+            token_b: T::AssetId,
+            amount_a: TokenBalance<T>,
+            min_liquidity: TokenBalance<T>,
+            max_amount_b: TokenBalance<T>,
+            deadline: T::BlockNumber,
+        ) -> dispatch::DispatchResult
+        {
+            let now = frame_system::Module::<T>::block_number();
+            ensure!(deadline > now, Error::<T>::Deadline);
+
+            let who = ensure_signed(origin)?;
+
+            ensure!(amount_a > Zero::zero(), Error::<T>::ZeroToken);
+            ensure!(max_amount_b > Zero::zero(), Error::<T>::ZeroToken);
+
+            let (pair_id, mut pair) = Self::get_pair_by_tokens(token_a, token_b)?;
+            Self::ensure_add_liquidity_allowed(pair_id)?;
+
+            let reserve_a = Self::to_u256(Self::get_pair_reserve(&pair, token_a))?;
+            let reserve_b = Self::to_u256(Self::get_pair_reserve(&pair, token_b))?;
+            let protocol_fee = Self::calculate_protocol_fee(pair_id, pair.liquidity_id, Self::get_pair_k(&pair)?)?;
+            let total_liquidity = Self::total_liquidity_with_fee(pair.liquidity_id, &protocol_fee)?;
+
+            let (amount_b, liquidity_minted): (TokenBalance<T>, TokenBalance<T>) = if total_liquidity > Zero::zero() {
+                ensure!(min_liquidity > Zero::zero(), Error::<T>::RequestedZeroLiquidity);
+                (
+                    Self::from_u256(Self::mul_div(Self::to_u256(amount_a)?, reserve_b, reserve_a)?)?,
+                    Self::from_u256(Self::mul_div(Self::to_u256(amount_a)?, Self::to_u256(total_liquidity)?, reserve_a)?)?,
+                )
+            } else {
+                // Fresh pair with no liquidity
+                let k = Self::to_u256(amount_a)?
+                    .checked_mul(Self::to_u256(max_amount_b)?)
+                    .ok_or(Error::<T>::Overflow)?;
+                (max_amount_b, Self::deduct_minimum_liquidity(Self::from_u256(Self::sqrt(k))?)?)
+            };
+
+            ensure!(max_amount_b >= amount_b, Error::<T>::TooManyToken);
+            ensure!(liquidity_minted >= min_liquidity, Error::<T>::TooLowLiquidity);
+
+            Self::mint_protocol_fee(pair_id, pair.liquidity_id, &protocol_fee)?;
+            if total_liquidity.is_zero() {
+                Self::lock_minimum_liquidity(pair.liquidity_id)?;
+            }
+            <zenlink_assets::Module<T>>::inner_transfer(&token_a, &who, &pair.account, amount_a)?;
+            <zenlink_assets::Module<T>>::inner_transfer(&token_b, &who, &pair.account, amount_b)?;
+            <zenlink_assets::Module<T>>::inner_mint(&pair.liquidity_id, &who, liquidity_minted)?;
+
+            let reserve = Self::pair_reserve_mut(&mut pair, token_a);
+            *reserve = Self::add_reserve(*reserve, amount_a)?;
+            let reserve = Self::pair_reserve_mut(&mut pair, token_b);
+            *reserve = Self::add_reserve(*reserve, amount_b)?;
+
+            Self::update_k_last(pair_id, Self::get_pair_k(&pair)?, &protocol_fee);
+            Self::put_pair(pair_id, pair)?;
+
+            Self::deposit_event(RawEvent::PairLiquidityAdded(pair_id, who, token_a, amount_a, token_b, amount_b));
+
+            Ok(())
+        }
+
+        /// Remove liquidity from specific pair liquidity pool in the form of burning
+        /// shares(ZLK), and withdrawing both tokens from the pair account in proportion.
+        ///
+        /// - `token_a`: The asset id of the token of the pair.
+        /// - `token_b`: The asset id of the other token of the pair.
+        /// - `zlk_to_burn`: Liquidity amount to remove.
+        /// - `min_amount_a`: Minimum token_a to withdraw.
+        /// - `min_amount_b`: Minimum token_b to withdraw.
+        /// - `deadline`: When to invalidate the transaction.
+        #[weight = T::WeightInfo::remove_pair_liquidity()]
+        #[transactional]
+        pub fn remove_pair_liquidity(origin,
+            token_a: T::AssetId,
+            token_b: T::AssetId,
+            zlk_to_burn: TokenBalance<T>,
+            min_amount_a: TokenBalance<T>,
+            min_amount_b: TokenBalance<T>,
+            deadline: T::BlockNumber,
+        ) -> dispatch::DispatchResult
+        {
+            let now = frame_system::Module::<T>::block_number();
+            ensure!(deadline > now, Error::<T>::Deadline);
+
+            let who = ensure_signed(origin)?;
+
+            ensure!(zlk_to_burn > Zero::zero(), Error::<T>::BurnZeroZLKShares);
+
+            let (pair_id, mut pair) = Self::get_pair_by_tokens(token_a, token_b)?;
+
+            let protocol_fee = Self::calculate_protocol_fee(pair_id, pair.liquidity_id, Self::get_pair_k(&pair)?)?;
+            let total_liquidity = Self::total_liquidity_with_fee(pair.liquidity_id, &protocol_fee)?;
+
+            ensure!(total_liquidity > Zero::zero(), Error::<T>::NoLiquidity);
+
+            let zlk_to_burn_u256 = Self::to_u256(zlk_to_burn)?;
+            let total_liquidity = Self::to_u256(total_liquidity)?;
+            let amount_a: TokenBalance<T> = Self::from_u256(
+                Self::mul_div(zlk_to_burn_u256, Self::to_u256(Self::get_pair_reserve(&pair, token_a))?, total_liquidity)?
+            )?;
+            let amount_b: TokenBalance<T> = Self::from_u256(
+                Self::mul_div(zlk_to_burn_u256, Self::to_u256(Self::get_pair_reserve(&pair, token_b))?, total_liquidity)?
+            )?;
+
+            ensure!(amount_a >= min_amount_a, Error::<T>::NotEnoughToken);
+            ensure!(amount_b >= min_amount_b, Error::<T>::NotEnoughToken);
+
+            Self::mint_protocol_fee(pair_id, pair.liquidity_id, &protocol_fee)?;
+            <zenlink_assets::Module<T>>::inner_burn(&pair.liquidity_id, &who, zlk_to_burn)?;
+            <zenlink_assets::Module<T>>::inner_transfer(&token_a, &pair.account, &who, amount_a)?;
+            <zenlink_assets::Module<T>>::inner_transfer(&token_b, &pair.account, &who, amount_b)?;
+
+            let reserve = Self::pair_reserve_mut(&mut pair, token_a);
+            *reserve = Self::sub_reserve(*reserve, amount_a)?;
+            let reserve = Self::pair_reserve_mut(&mut pair, token_b);
+            *reserve = Self::sub_reserve(*reserve, amount_b)?;
+
+            Self::update_k_last(pair_id, Self::get_pair_k(&pair)?, &protocol_fee);
+            Self::put_pair(pair_id, pair)?;
+
+            Self::deposit_event(RawEvent::PairLiquidityRemoved(pair_id, who, token_a, amount_a, token_b, amount_b));
+
+            Ok(())
+        }
+
+        /// Swap token to other token in the pair directly.
+        ///
+        /// User specifies the exact amount of token to sold and the amount not less the minimum
+        /// other token to be returned.
+        /// - `token_id`: The asset id of the token to be sold.
+        /// - `other_token_id`: The asset id of the other token to be bought.
+        /// - `token_sold`: The token balance amount to be sold.
+        /// - `min_other_token`: The minimum other token expected to buy.
+        /// - `deadline`: When to invalidate the transaction.
+        /// - `recipient`: Receiver of the bought other token.
+        #[weight = T::WeightInfo::token_to_token_pair_input()]
+        #[transactional]
+        pub fn token_to_token_pair_input(origin,
+            token_id: T::AssetId,
+            other_token_id: T::AssetId,
+            token_sold: TokenBalance<T>,
+            min_other_token: TokenBalance<T>,
+            deadline: T::BlockNumber,
+            recipient: T::AccountId,
+        ) -> dispatch::DispatchResult
+        {
+            let now = frame_system::Module::<T>::block_number();
+            ensure!(deadline >= now, Error::<T>::Deadline);
+
+            let buyer = ensure_signed(origin)?;
+
+            ensure!(token_sold > Zero::zero(), Error::<T>::ZeroToken);
+            ensure!(min_other_token > Zero::zero(), Error::<T>::ZeroToken);
+
+            let (pair_id, mut pair) = Self::get_pair_by_tokens(token_id, other_token_id)?;
+            Self::ensure_swap_allowed(pair_id)?;
+
+            let other_token_bought = Self::get_pair_token_to_token_input_price(&pair, token_id, token_sold)?;
+
+            ensure!(other_token_bought >= min_other_token, Error::<T>::NotEnoughToken);
+
+            <zenlink_assets::Module<T>>::inner_transfer(&token_id, &buyer, &pair.account, token_sold)?;
+            <zenlink_assets::Module<T>>::inner_transfer(&other_token_id, &pair.account, &recipient, other_token_bought)?;
+
+            let reserve = Self::pair_reserve_mut(&mut pair, token_id);
+            *reserve = Self::add_reserve(*reserve, token_sold)?;
+            let reserve = Self::pair_reserve_mut(&mut pair, other_token_id);
+            *reserve = Self::sub_reserve(*reserve, other_token_bought)?;
+            Self::put_pair(pair_id, pair)?;
+
+            Self::deposit_event(RawEvent::PairTokenPurchase(pair_id, buyer, token_id, token_sold, other_token_id, other_token_bought, recipient));
+
+            Ok(())
+        }
+
+        /// Swap token to other token in the pair directly.
+        ///
+        /// User specifies the maximum token to be sold and the exact
+        /// other token to be returned.
+        /// - `token_id`: The asset id of the token to be sold.
+        /// - `other_token_id`: The asset id of the other token to be bought.
+        /// - `other_token_bought`: The amount of the other token to buy.
+        /// - `max_token`: The maximum token expected to be sold.
+        /// - `deadline`: When to invalidate the transaction.
+        /// - `recipient`: Receiver of the bought other token.
+        #[weight = T::WeightInfo::token_to_token_pair_output()]
+        #[transactional]
+        pub fn token_to_token_pair_output(origin,
+            token_id: T::AssetId,
+            other_token_id: T::AssetId,
+            other_token_bought: TokenBalance<T>,
+            max_token: TokenBalance<T>,
+            deadline: T::BlockNumber,
+            recipient: T::AccountId,
+        ) -> dispatch::DispatchResult
+        {
+            let now = frame_system::Module::<T>::block_number();
+            ensure!(deadline >= now, Error::<T>::Deadline);
+
+            let buyer = ensure_signed(origin)?;
+
+            ensure!(other_token_bought > Zero::zero(), Error::<T>::ZeroToken);
+            ensure!(max_token > Zero::zero(), Error::<T>::ZeroToken);
+
+            let (pair_id, mut pair) = Self::get_pair_by_tokens(token_id, other_token_id)?;
+            Self::ensure_swap_allowed(pair_id)?;
+
+            let token_sold = Self::get_pair_token_to_token_output_price(&pair, token_id, other_token_bought)?;
+
+            ensure!(max_token >= token_sold, Error::<T>::TooExpensiveToken);
+
+            <zenlink_assets::Module<T>>::inner_transfer(&token_id, &buyer, &pair.account, token_sold)?;
+            <zenlink_assets::Module<T>>::inner_transfer(&other_token_id, &pair.account, &recipient, other_token_bought)?;
+
+            let reserve = Self::pair_reserve_mut(&mut pair, token_id);
+            *reserve = Self::add_reserve(*reserve, token_sold)?;
+            let reserve = Self::pair_reserve_mut(&mut pair, other_token_id);
+            *reserve = Self::sub_reserve(*reserve, other_token_bought)?;
+            Self::put_pair(pair_id, pair)?;
+
+            Self::deposit_event(RawEvent::PairTokenPurchase(pair_id, buyer, token_id, token_sold, other_token_id, other_token_bought, recipient));
+
+            Ok(())
+        }
+
+        /// Swap along the path of exchanges and pairs.
+        ///
+        /// User specifies the exact amount of input asset to sold and the amount not less the
+        /// minimum output asset to be returned.
+        /// - `asset_in`: The asset to be sold, native currency or token.
+        /// - `path`: The exchanges and pairs to swap in by order.
+        /// - `amount_in`: The amount of input asset to be sold.
+        /// - `min_amount_out`: The minimum output asset expected to buy.
+        /// - `deadline`: When to invalidate the transaction.
+        /// - `recipient`: Receiver of the bought output asset.
+        #[weight = T::WeightInfo::swap_exact_in_by_path(path.len() as u32)]
+        #[transactional]
+        pub fn swap_exact_in_by_path(origin,
+            asset_in: SwapAssetOf<T>,
+            path: Vec<SwapHandlerOf<T>>,
+            amount_in: AssetBalance,
+            min_amount_out: AssetBalance,
+            deadline: T::BlockNumber,
+            recipient: T::AccountId,
+        ) -> dispatch::DispatchResult
+        {
+            let now = frame_system::Module::<T>::block_number();
+            ensure!(deadline >= now, Error::<T>::Deadline);
+
+            let buyer = ensure_signed(origin)?;
+
+            ensure!(path.len() <= MAX_PATH as usize, Error::<T>::PathTooLong);
+
+            let hops = Self::get_swap_hops(asset_in, &path)?;
+            let asset_out = hops[hops.len() - 1].asset_out;
+
+            ensure!(amount_in > Zero::zero(), Self::zero_amount_error(asset_in));
+            ensure!(min_amount_out > Zero::zero(), Self::zero_amount_error(asset_out));
+
+            let amounts = Self::get_amounts_out(&hops, Self::to_u256(amount_in)?)?;
+            let amount_out: AssetBalance = Self::from_u256(amounts[amounts.len() - 1])?;
+
+            ensure!(amount_out >= min_amount_out, Error::<T>::NotEnoughOutput);
+
+            Self::swap_by_path(&buyer, &hops, &amounts, &recipient)?;
+
+            Self::deposit_event(RawEvent::PathSwapped(buyer, asset_in, amount_in, asset_out, amount_out, recipient));
+
+            Ok(())
+        }
+
+        /// Swap along the path of exchanges and pairs.
+        ///
+        /// User specifies the maximum input asset to be sold and the exact
+        /// output asset to be returned.
+        /// - `asset_in`: The asset to be sold, native currency or token.
+        /// - `path`: The exchanges and pairs to swap in by order.
+        /// - `amount_out`: The amount of output asset to buy.
+        /// - `max_amount_in`: The maximum input asset expected to be sold.
+        /// - `deadline`: When to invalidate the transaction.
+        /// - `recipient`: Receiver of the bought output asset.
+        #[weight = T::WeightInfo::swap_exact_out_by_path(path.len() as u32)]
+        #[transactional]
+        pub fn swap_exact_out_by_path(origin,
+            asset_in: SwapAssetOf<T>,
+            path: Vec<SwapHandlerOf<T>>,
+            amount_out: AssetBalance,
+            max_amount_in: AssetBalance,
+            deadline: T::BlockNumber,
+            recipient: T::AccountId,
+        ) -> dispatch::DispatchResult
+        {
+            let now = frame_system::Module::<T>::block_number();
+            ensure!(deadline >= now, Error::<T>::Deadline);
+
+            let buyer = ensure_signed(origin)?;
+
+            ensure!(path.len() <= MAX_PATH as usize, Error::<T>::PathTooLong);
+
+            let hops = Self::get_swap_hops(asset_in, &path)?;
+            let asset_out = hops[hops.len() - 1].asset_out;
+
+            ensure!(amount_out > Zero::zero(), Self::zero_amount_error(asset_out));
+            ensure!(max_amount_in > Zero::zero(), Self::zero_amount_error(asset_in));
+
+            let amounts = Self::get_amounts_in(&hops, Self::to_u256(amount_out)?)?;
+            let amount_in: AssetBalance = Self::from_u256(amounts[0])?;
+
+            ensure!(max_amount_in >= amount_in, Error::<T>::TooExpensiveInput);
+
+            Self::swap_by_path(&buyer, &hops, &amounts, &recipient)?;
+
+            Self::deposit_event(RawEvent::PathSwapped(buyer, asset_in, amount_in, asset_out, amount_out, recipient));
+
+            Ok(())
+        }
+
+        /// Force the reserves of specific exchange or pair to match the balances of its account.
+        ///
+        /// - `swap_handler`: The wrapper of exchangeId and assetId to access, a pair is accessed by exchangeId.
+        #[weight = T::WeightInfo::sync()]
+        #[transactional]
+        pub fn sync(origin,
+            swap_handler: SwapHandlerOf<T>,
+        ) -> dispatch::DispatchResult
+        {
+            ensure_signed(origin)?;
+
+            let exchange_id = Self::get_exchange_id(&swap_handler)?;
+
+            if let Some(mut exchange) = Self::get_exchange(exchange_id) {
+                exchange.currency_reserve = T::Currency::free_balance(&exchange.account);
+                exchange.token_reserve = <zenlink_assets::Module<T>>::balance_of(&exchange.token_id, &exchange.account);
+
+                Self::deposit_event(RawEvent::ExchangeSynced(exchange_id, exchange.currency_reserve, exchange.token_reserve));
+
+                Self::put_exchange(exchange_id, exchange)?;
+            } else if let Some(mut pair) = Self::get_pair(exchange_id) {
+                pair.reserve_0 = <zenlink_assets::Module<T>>::balance_of(&pair.token_0, &pair.account);
+                pair.reserve_1 = <zenlink_assets::Module<T>>::balance_of(&pair.token_1, &pair.account);
+
+                Self::deposit_event(RawEvent::PairSynced(exchange_id, pair.reserve_0, pair.reserve_1));
+
+                Self::put_pair(exchange_id, pair)?;
+            } else {
+                return Err(Error::<T>::ExchangeNotExists.into());
+            }
+
+            Ok(())
+        }
+
+        /// Transfer the balances of specific exchange or pair account over its reserves to the recipient.
+        ///
+        /// - `swap_handler`: The wrapper of exchangeId and assetId to access, a pair is accessed by exchangeId.
+        /// - `recipient`: Receiver of the surplus.
+        #[weight = T::WeightInfo::skim()]
+        #[transactional]
+        pub fn skim(origin,
+            swap_handler: SwapHandlerOf<T>,
+            recipient: T::AccountId,
+        ) -> dispatch::DispatchResult
+        {
+            ensure_signed(origin)?;
+
+            let exchange_id = Self::get_exchange_id(&swap_handler)?;
+
+            if let Some(exchange) = Self::get_exchange(exchange_id) {
+                let currency_surplus = T::Currency::free_balance(&exchange.account).saturating_sub(exchange.currency_reserve);
+                let token_surplus = <zenlink_assets::Module<T>>::balance_of(&exchange.token_id, &exchange.account)
+                    .saturating_sub(exchange.token_reserve);
+
+                if !currency_surplus.is_zero() {
+                    // Keep the exchange account alive while it holds the currency reserve.
+                    let existence_requirement = if exchange.currency_reserve.is_zero() {
+                        ExistenceRequirement::AllowDeath
+                    } else {
+                        ExistenceRequirement::KeepAlive
+                    };
+                    T::Currency::transfer(&exchange.account, &recipient, currency_surplus, existence_requirement)?;
+                }
+                if !token_surplus.is_zero() {
+                    <zenlink_assets::Module<T>>::inner_transfer(&exchange.token_id, &exchange.account, &recipient, token_surplus)?;
+                }
+
+                Self::deposit_event(RawEvent::ExchangeSkimmed(exchange_id, recipient, currency_surplus, token_surplus));
+            } else if let Some(pair) = Self::get_pair(exchange_id) {
+                let surplus_0 = <zenlink_assets::Module<T>>::balance_of(&pair.token_0, &pair.account).saturating_sub(pair.reserve_0);
+                let surplus_1 = <zenlink_assets::Module<T>>::balance_of(&pair.token_1, &pair.account).saturating_sub(pair.reserve_1);
+
+                if !surplus_0.is_zero() {
+                    <zenlink_assets::Module<T>>::inner_transfer(&pair.token_0, &pair.account, &recipient, surplus_0)?;
+                }
+                if !surplus_1.is_zero() {
+                    <zenlink_assets::Module<T>>::inner_transfer(&pair.token_1, &pair.account, &recipient, surplus_1)?;
+                }
+
+                Self::deposit_event(RawEvent::PairSkimmed(exchange_id, recipient, surplus_0, surplus_1));
+            } else {
+                return Err(Error::<T>::ExchangeNotExists.into());
+            }
+
+            Ok(())
+        }
+
+        /// Close specific exchange or pair which has no liquidity held by providers, only the
+        /// `MinimumLiquidity` locked on the first deposit is left. Destroy its liquidity token and
+        /// sweep the balances left in the exchange account to the recipient.
+        /// A new exchange or pair could be created for the tokens afterwards.
+        ///
+        /// - `swap_handler`: The wrapper of exchangeId and assetId to access, a pair is accessed by exchangeId.
+        /// - `recipient`: Receiver of the balances left in the exchange account.
+        #[weight = T::WeightInfo::close_exchange()]
+        #[transactional]
+        pub fn close_exchange(origin,
+            swap_handler: SwapHandlerOf<T>,
+            recipient: T::AccountId,
+        ) -> dispatch::DispatchResult
+        {
+            let _ = ensure_signed(origin)?;
+
+            let exchange_id = Self::get_exchange_id(&swap_handler)?;
+
+            if let Some(exchange) = Self::get_exchange(exchange_id) {
+                Self::ensure_only_locked_liquidity(exchange.liquidity_id)?;
+                Self::do_close_exchange(exchange_id, exchange, &recipient)
+            } else if let Some(pair) = Self::get_pair(exchange_id) {
+                Self::ensure_only_locked_liquidity(pair.liquidity_id)?;
+                Self::do_close_pair(exchange_id, pair, &recipient)
+            } else {
+                Err(Error::<T>::ExchangeNotExists.into())
+            }
+        }
+
+        /// Close specific exchange or pair even if its liquidity is still held by providers, destroy
+        /// its liquidity token and sweep the balances of the exchange account to the recipient.
+        /// The liquidity token left to the providers can't be transferred or redeemed any more.
+        ///
+        /// The dispatch origin for this call must be `ControlOrigin`.
+        ///
+        /// - `swap_handler`: The wrapper of exchangeId and assetId to access, a pair is accessed by exchangeId.
+        /// - `recipient`: Receiver of the balances of the exchange account.
+        #[weight = T::WeightInfo::force_close_exchange()]
+        #[transactional]
+        pub fn force_close_exchange(origin,
+            swap_handler: SwapHandlerOf<T>,
+            recipient: T::AccountId,
+        ) -> dispatch::DispatchResult
+        {
+            T::ControlOrigin::ensure_origin(origin)?;
+
+            let exchange_id = Self::get_exchange_id(&swap_handler)?;
+
+            if let Some(exchange) = Self::get_exchange(exchange_id) {
+                Self::do_close_exchange(exchange_id, exchange, &recipient)
+            } else if let Some(pair) = Self::get_pair(exchange_id) {
+                Self::do_close_pair(exchange_id, pair, &recipient)
+            } else {
+                Err(Error::<T>::ExchangeNotExists.into())
+            }
+        }
+
+        /// Borrow currency and token from specific exchange, and repay them with the fee
+        /// in the `FlashSwapHandler` within the same call.
+        ///
+        /// - `swap_handler`: The wrapper of exchangeId and assetId to access.
+        /// - `currency_amount`: The amount of currency to borrow.
+        /// - `token_amount`: The amount of token to borrow.
+        /// - `receiver`: Receiver of the borrowed currency and token.
+        /// - `data`: The data passed to the `FlashSwapHandler`.
+        #[weight = T::WeightInfo::flash_swap().saturating_add(T::FlashSwapHandler::on_flash_swap_weight(data))]
+        #[transactional]
+        pub fn flash_swap(origin,
+            swap_handler: SwapHandlerOf<T>,
+            currency_amount: BalanceOf<T>,
+            token_amount: TokenBalance<T>,
+            receiver: T::AccountId,
+            data: Vec<u8>,
+        ) -> dispatch::DispatchResult
+        {
+            let who = ensure_signed(origin)?;
+
+            let exchange_id = Self::get_exchange_id(&swap_handler)?;
+
+            Self::do_flash_swap(&who, exchange_id, currency_amount, token_amount, &receiver, &data)
+        }
+    }
+}
+
+impl<T: Trait> Module<T> {
+    /// Get the exchange_id by unwrapping the swap_handler.
+    /// Return exist exchange_id or `ExchangeNotExists` error, `Locked` error while the flash
+    /// swap of the exchange is in progress.
+    pub fn get_exchange_id(swap_handler: &SwapHandlerOf<T>) -> Result<T::ExchangeId, Error<T>> {
+        let exchange_id = match swap_handler {
+            SwapHandler::ExchangeId(exchange_id) => *exchange_id,
+            SwapHandler::AssetId(asset_id) => {
+                Self::token_to_exchange(asset_id).ok_or(Error::<T>::ExchangeNotExists)?
+            }
+        };
+        ensure!(!Self::flash_swap_locked(exchange_id), Error::<T>::Locked);
+
+        Ok(exchange_id)
+    }
+
+    pub fn get_exchange_info(id :T::ExchangeId) -> Option<ExchangeOf<T>> {
+        Self::get_exchange(id)
+    }
+
+    /// Create the exchange of the token with its liquidity token, return the exchange id.
+    fn do_create_exchange(token_id: T::AssetId) -> Result<T::ExchangeId, dispatch::DispatchError> {
+        let asset_info = <zenlink_assets::Module<T>>::asset_info(&token_id);
+        ensure!(asset_info.is_some(), Error::<T>::TokenNotExists);
+        ensure!(Self::zlk_to_exchange(token_id).is_none(), Error::<T>::DeniedSwap);
+        ensure!(Self::token_to_exchange(token_id).is_none(), Error::<T>::ExchangeAlreadyExists);
+
+        let exchange_id = Self::next_exchange_id();
+        let next_id = exchange_id.checked_add(&One::one())
+            .ok_or("Overflow")?;
+
+        let account: T::AccountId = T::ModuleId::get().into_sub_account(exchange_id);
+
+        // create a new lp token for exchange
+        let liquidity_id = <zenlink_assets::Module<T>>::inner_issue(&account, Zero::zero(), ZLK);
+        let new_exchange = Exchange {
+            token_id,
+            liquidity_id,
+            account: account.clone(),
+            fee_rate: None,
+            token_reserve: Zero::zero(),
+            currency_reserve: Zero::zero(),
+        };
+
+        <TokenToExchange<T>>::insert(token_id, exchange_id);
+        <ZLKToExchange<T>>::insert(liquidity_id, exchange_id);
+        <Exchanges<T>>::insert(exchange_id, new_exchange);
+        <NextExchangeId<T>>::put(next_id);
+
+        Self::deposit_event(RawEvent::ExchangeCreated(exchange_id, account));
+
+        Ok(exchange_id)
+    }
+
+    /// Deposit the currency and the token in proportion to the reserves, at most `max_token`,
+    /// and mint the liquidity to the provider. The fresh exchange takes `max_token` as the ratio.
+    /// Return the currency and token deposited.
+    fn do_add_liquidity(
+        who: &T::AccountId,
+        exchange_id: T::ExchangeId,
+        currency_amount: BalanceOf<T>,
+        min_liquidity: TokenBalance<T>,
+        max_token: TokenBalance<T>,
+    ) -> Result<(BalanceOf<T>, TokenBalance<T>), dispatch::DispatchError> {
+        let mut exchange = Self::get_exchange(exchange_id).ok_or(Error::<T>::ExchangeNotExists)?;
+        let protocol_fee = Self::calculate_protocol_fee(exchange_id, exchange.liquidity_id, Self::get_k(&exchange)?)?;
+        let total_liquidity = Self::total_liquidity_with_fee(exchange.liquidity_id, &protocol_fee)?;
+
+        let token_amount = if total_liquidity > Zero::zero() {
+            ensure!(min_liquidity > Zero::zero(), Error::<T>::RequestedZeroLiquidity);
+            let currency_reserve = Self::to_u256(Self::get_currency_reserve(&exchange))?;
+            let token_reserve = Self::to_u256(Self::get_token_reserve(&exchange))?;
+            let token_amount: TokenBalance<T> = Self::from_u256(
+                Self::mul_div(Self::to_u256(currency_amount)?, token_reserve, currency_reserve)?
+            )?;
+            let liquidity_minted: TokenBalance<T> = Self::from_u256(
+                Self::mul_div(Self::to_u256(currency_amount)?, Self::to_u256(total_liquidity)?, currency_reserve)?
+            )?;
+
+            ensure!(max_token >= token_amount, Error::<T>::TooManyToken);
+            ensure!(liquidity_minted >= min_liquidity, Error::<T>::TooLowLiquidity);
+
+            Self::mint_protocol_fee(exchange_id, exchange.liquidity_id, &protocol_fee)?;
+            T::Currency::transfer(who, &exchange.account, currency_amount, ExistenceRequirement::KeepAlive)?;
+            <zenlink_assets::Module<T>>::inner_mint(&exchange.liquidity_id, who, liquidity_minted)?;
+            <zenlink_assets::Module<T>>::inner_transfer(&exchange.token_id, who, &exchange.account, token_amount)?;
+
+            token_amount
+        } else {
+            // Fresh exchange with no liquidity
+            let token_amount = max_token;
+            // Any currency donated to the exchange account is not counted in.
+            let initial_liquidity: TokenBalance<T> = Self::from_u256(Self::to_u256(currency_amount)?)?;
+            let liquidity_minted = Self::deduct_minimum_liquidity(initial_liquidity)?;
+
+            T::Currency::transfer(who, &exchange.account, currency_amount, ExistenceRequirement::KeepAlive)?;
+
+            Self::lock_minimum_liquidity(exchange.liquidity_id)?;
+            <zenlink_assets::Module<T>>::inner_mint(&exchange.liquidity_id, who, liquidity_minted)?;
+            <zenlink_assets::Module<T>>::inner_transfer(&exchange.token_id, who, &exchange.account, token_amount)?;
+
+            token_amount
+        };
+
+        exchange.currency_reserve = Self::add_reserve(exchange.currency_reserve, currency_amount)?;
+        exchange.token_reserve = Self::add_reserve(exchange.token_reserve, token_amount)?;
+
+        Self::update_k_last(exchange_id, Self::get_k(&exchange)?, &protocol_fee);
+        Self::put_exchange(exchange_id, exchange)?;
+
+        Self::deposit_event(RawEvent::LiquidityAdded(exchange_id, who.clone(), currency_amount, token_amount));
+
+        Ok((currency_amount, token_amount))
+    }
+
+    /// The currency and token to deposit by the desired amounts at the current price,
+    /// the desired amounts of a fresh exchange set the price.
+    pub fn get_liquidity_amounts(
+        exchange: &ExchangeOf<T>,
+        currency_desired: BalanceOf<T>,
+        token_desired: TokenBalance<T>,
+        currency_min: BalanceOf<T>,
+        token_min: TokenBalance<T>,
+    ) -> Result<(BalanceOf<T>, TokenBalance<T>), Error<T>> {
+        let currency_reserve = Self::get_currency_reserve(exchange);
+        let token_reserve = Self::get_token_reserve(exchange);
+        if currency_reserve.is_zero() && token_reserve.is_zero() {
+            return Ok((currency_desired, token_desired));
+        }
+
+        let currency_reserve = Self::to_u256(currency_reserve)?;
+        let token_reserve = Self::to_u256(token_reserve)?;
+        let token_optimal: TokenBalance<T> = Self::from_u256(
+            Self::mul_div(Self::to_u256(currency_desired)?, token_reserve, currency_reserve)?
+        )?;
+        if token_optimal <= token_desired {
+            ensure!(token_optimal >= token_min, Error::<T>::NotEnoughToken);
+            Ok((currency_desired, token_optimal))
+        } else {
+            let currency_optimal: BalanceOf<T> = Self::from_u256(
+                Self::mul_div(Self::to_u256(token_desired)?, currency_reserve, token_reserve)?
+            )?;
+            ensure!(currency_optimal >= currency_min, Error::<T>::NotEnoughCurrency);
+            Ok((currency_optimal, token_desired))
+        }
+    }
+
+    /// Swap the optimal part of the single asset in the exchange, then deposit the rest with
+    /// the bought asset, which stays in the exchange account, as liquidity.
+    /// Return the liquidity minted.
+    fn do_add_liquidity_single_sided(
+        who: &T::AccountId,
+        exchange_id: T::ExchangeId,
+        asset_in: SwapAssetOf<T>,
+        amount_in: AssetBalance,
+        min_liquidity: TokenBalance<T>,
+    ) -> Result<TokenBalance<T>, dispatch::DispatchError> {
+        let mut exchange = Self::get_exchange(exchange_id).ok_or(Error::<T>::ExchangeNotExists)?;
+        let is_currency_in = Self::is_currency_of(&exchange, asset_in)?;
+
+        let currency_reserve = Self::to_u256(Self::get_currency_reserve(&exchange))?;
+        let token_reserve = Self::to_u256(Self::get_token_reserve(&exchange))?;
+        ensure!(!currency_reserve.is_zero() && !token_reserve.is_zero(), Error::<T>::NoLiquidity);
+
+        let (reserve_in, reserve_out) = if is_currency_in {
+            (currency_reserve, token_reserve)
+        } else {
+            (token_reserve, currency_reserve)
+        };
+        let fee_rate = Self::fee_rate_of(&exchange);
+        let amount_in = Self::to_u256(amount_in)?;
+        let amount_swapped = Self::get_single_sided_swap_amount(amount_in, reserve_in, fee_rate)?;
+        let amount_bought = Self::get_input_price(amount_swapped, reserve_in, reserve_out, fee_rate)?;
+
+        Self::transfer_swap_asset(asset_in, who, &exchange.account, amount_in, ExistenceRequirement::KeepAlive)?;
+
+        // The reserves after the swap, the protocol fee counts in the growth of k by it.
+        let reserve_in = reserve_in + amount_swapped;
+        let reserve_out = reserve_out - amount_bought;
+        Self::set_reserves_by_side(&mut exchange, is_currency_in, reserve_in, reserve_out)?;
+
+        let protocol_fee = Self::calculate_protocol_fee(exchange_id, exchange.liquidity_id, Self::get_k(&exchange)?)?;
+        let total_liquidity = Self::to_u256(Self::total_liquidity_with_fee(exchange.liquidity_id, &protocol_fee)?)?;
+        ensure!(!total_liquidity.is_zero(), Error::<T>::NoLiquidity);
+
+        // Any surplus of one asset over the price is left to the exchange.
+        let amount_deposited = amount_in - amount_swapped;
+        let liquidity_minted: TokenBalance<T> = Self::from_u256(
+            Self::mul_div(amount_deposited, total_liquidity, reserve_in)?
+                .min(Self::mul_div(amount_bought, total_liquidity, reserve_out)?)
+        )?;
+        ensure!(liquidity_minted >= min_liquidity, Error::<T>::TooLowLiquidity);
+
+        Self::mint_protocol_fee(exchange_id, exchange.liquidity_id, &protocol_fee)?;
+        <zenlink_assets::Module<T>>::inner_mint(&exchange.liquidity_id, who, liquidity_minted)?;
+
+        Self::set_reserves_by_side(&mut exchange, is_currency_in, reserve_in + amount_deposited, reserve_out + amount_bought)?;
+
+        Self::update_k_last(exchange_id, Self::get_k(&exchange)?, &protocol_fee);
+        Self::put_exchange(exchange_id, exchange)?;
+
+        Ok(liquidity_minted)
+    }
+
+    /// Burn the liquidity, then swap the withdrawn other asset to the single asset in the exchange.
+    /// Return the amount of the single asset withdrawn.
+    fn do_remove_liquidity_single_sided(
+        who: &T::AccountId,
+        exchange_id: T::ExchangeId,
+        zlk_to_burn: TokenBalance<T>,
+        asset_out: SwapAssetOf<T>,
+        min_amount_out: AssetBalance,
+    ) -> Result<AssetBalance, dispatch::DispatchError> {
+        let mut exchange = Self::get_exchange(exchange_id).ok_or(Error::<T>::ExchangeNotExists)?;
+        let is_currency_out = Self::is_currency_of(&exchange, asset_out)?;
+
+        let protocol_fee = Self::calculate_protocol_fee(exchange_id, exchange.liquidity_id, Self::get_k(&exchange)?)?;
+        let total_liquidity = Self::to_u256(Self::total_liquidity_with_fee(exchange.liquidity_id, &protocol_fee)?)?;
+        ensure!(!total_liquidity.is_zero(), Error::<T>::NoLiquidity);
+
+        let currency_reserve = Self::to_u256(Self::get_currency_reserve(&exchange))?;
+        let token_reserve = Self::to_u256(Self::get_token_reserve(&exchange))?;
+        let (reserve_in, reserve_out) = if is_currency_out {
+            (token_reserve, currency_reserve)
+        } else {
+            (currency_reserve, token_reserve)
+        };
+        let zlk_to_burn_u256 = Self::to_u256(zlk_to_burn)?;
+        let amount_withdrawn_in = Self::mul_div(zlk_to_burn_u256, reserve_in, total_liquidity)?;
+        let amount_withdrawn_out = Self::mul_div(zlk_to_burn_u256, reserve_out, total_liquidity)?;
+
+        Self::mint_protocol_fee(exchange_id, exchange.liquidity_id, &protocol_fee)?;
+        <zenlink_assets::Module<T>>::inner_burn(&exchange.liquidity_id, who, zlk_to_burn)?;
+
+        // The k after the burn, the swap below grows it as a swap of its own.
+        let reserve_in = reserve_in - amount_withdrawn_in;
+        let reserve_out = reserve_out - amount_withdrawn_out;
+        Self::set_reserves_by_side(&mut exchange, !is_currency_out, reserve_in, reserve_out)?;
+        Self::update_k_last(exchange_id, Self::get_k(&exchange)?, &protocol_fee);
+
+        let amount_bought = Self::get_input_price(amount_withdrawn_in, reserve_in, reserve_out, Self::fee_rate_of(&exchange))?;
+        let amount_out = amount_withdrawn_out + amount_bought;
+        ensure!(amount_out >= Self::to_u256(min_amount_out)?, Error::<T>::NotEnoughOutput);
+
+        Self::transfer_swap_asset(asset_out, &exchange.account, who, amount_out, ExistenceRequirement::AllowDeath)?;
+
+        Self::set_reserves_by_side(&mut exchange, !is_currency_out, reserve_in + amount_withdrawn_in, reserve_out - amount_bought)?;
+        Self::put_exchange(exchange_id, exchange)?;
+
+        Self::from_u256(amount_out).map_err(Into::into)
+    }
+
+    /// The part of the single asset to swap, so that the rest matches the bought asset at the price
+    /// after the swap: `(sqrt(((1 + r) * R)^2 + 4 * r * R * a) - (1 + r) * R) / (2 * r)`,
+    /// where `r` is the fraction of input kept after fee.
+    fn get_single_sided_swap_amount(amount_in: U256, reserve_in: U256, fee_rate: Permill) -> Result<U256, Error<T>> {
+        let (fee_numerator, fee_denominator) = Self::fee_fraction(fee_rate);
+        let b = reserve_in
+            .checked_mul(fee_numerator + fee_denominator)
+            .ok_or(Error::<T>::Overflow)?;
+        let c = U256::from(4u8)
+            .checked_mul(fee_numerator * fee_denominator)
+            .and_then(|n| n.checked_mul(reserve_in))
+            .and_then(|n| n.checked_mul(amount_in))
+            .ok_or(Error::<T>::Overflow)?;
+        let root = b
+            .checked_mul(b)
+            .and_then(|n| n.checked_add(c))
+            .map(Self::sqrt)
+            .ok_or(Error::<T>::Overflow)?;
+
+        // The default `ExchangeFeeRate` isn't checked as the set fee rates, the rate of 100% keeps no input.
+        (root - b)
+            .checked_div(fee_numerator * U256::from(2u8))
+            .ok_or(Error::<T>::InvalidFeeRate)
+    }
+
+    /// Whether the asset is the native currency or the token of the exchange.
+    fn is_currency_of(exchange: &ExchangeOf<T>, asset: SwapAssetOf<T>) -> Result<bool, Error<T>> {
+        match asset {
+            SwapAsset::Currency => Ok(true),
+            SwapAsset::Token(token_id) if token_id == exchange.token_id => Ok(false),
+            _ => Err(Error::<T>::InvalidAsset),
+        }
+    }
+
+    /// Set the reserves of the exchange, the first one is the currency reserve if `is_currency_first`.
+    fn set_reserves_by_side(
+        exchange: &mut ExchangeOf<T>,
+        is_currency_first: bool,
+        reserve_first: U256,
+        reserve_second: U256,
+    ) -> Result<(), Error<T>> {
+        let (currency_reserve, token_reserve) = if is_currency_first {
+            (reserve_first, reserve_second)
+        } else {
+            (reserve_second, reserve_first)
+        };
+        exchange.currency_reserve = Self::from_u256(currency_reserve)?;
+        exchange.token_reserve = Self::from_u256(token_reserve)?;
+
+        Ok(())
+    }
+
+    /// Ensure no liquidity is held by providers, only the `MinimumLiquidity` locked is left.
+    fn ensure_only_locked_liquidity(liquidity_id: T::AssetId) -> dispatch::DispatchResult {
+        let total_liquidity = <zenlink_assets::Module<T>>::total_supply(&liquidity_id);
+        let locked_liquidity = <zenlink_assets::Module<T>>::balance_of(&liquidity_id, &Self::minimum_liquidity_account());
+        ensure!(total_liquidity == locked_liquidity, Error::<T>::LiquidityOutstanding);
+
+        Ok(())
+    }
+
+    /// Burn the locked liquidity and destroy the liquidity token of the closed exchange or pair.
+    fn destroy_liquidity(liquidity_id: T::AssetId) -> dispatch::DispatchResult {
+        let locked_liquidity = <zenlink_assets::Module<T>>::balance_of(&liquidity_id, &Self::minimum_liquidity_account());
+        if !locked_liquidity.is_zero() {
+            <zenlink_assets::Module<T>>::inner_burn(&liquidity_id, &Self::minimum_liquidity_account(), locked_liquidity)?;
+        }
+        <zenlink_assets::Module<T>>::inner_destroy(&liquidity_id)
+    }
+
+    /// Remove the records shared by exchanges and pairs of the closed one.
+    fn remove_exchange_records(exchange_id: T::ExchangeId, liquidity_id: T::AssetId) {
+        <ZLKToExchange<T>>::remove(liquidity_id);
+        <KLast<T>>::remove(exchange_id);
+        <PriceCumulatives<T>>::remove(exchange_id);
+        <PriceObservations<T>>::remove(exchange_id);
+        <ExchangeStatuses<T>>::remove(exchange_id);
+    }
+
+    /// Remove the exchange with its mappings and records, destroy its liquidity token,
+    /// and sweep the balances of the exchange account to the recipient.
+    fn do_close_exchange(
+        exchange_id: T::ExchangeId,
+        exchange: ExchangeOf<T>,
+        recipient: &T::AccountId,
+    ) -> dispatch::DispatchResult {
+        Self::destroy_liquidity(exchange.liquidity_id)?;
+
+        let currency_amount = T::Currency::free_balance(&exchange.account);
+        if !currency_amount.is_zero() {
+            T::Currency::transfer(&exchange.account, recipient, currency_amount, ExistenceRequirement::AllowDeath)?;
+        }
+        let token_amount = <zenlink_assets::Module<T>>::balance_of(&exchange.token_id, &exchange.account);
+        if !token_amount.is_zero() {
+            <zenlink_assets::Module<T>>::inner_transfer(&exchange.token_id, &exchange.account, recipient, token_amount)?;
+        }
+
+        <TokenToExchange<T>>::remove(exchange.token_id);
+        <Exchanges<T>>::remove(exchange_id);
+        Self::remove_exchange_records(exchange_id, exchange.liquidity_id);
+
+        Self::deposit_event(RawEvent::ExchangeClosed(exchange_id, recipient.clone(), currency_amount, token_amount));
+
+        Ok(())
+    }
+
+    /// Remove the pair with its mappings and records, destroy its liquidity token,
+    /// and sweep the balances of the pair account to the recipient.
+    fn do_close_pair(
+        pair_id: T::ExchangeId,
+        pair: PairOf<T>,
+        recipient: &T::AccountId,
+    ) -> dispatch::DispatchResult {
+        Self::destroy_liquidity(pair.liquidity_id)?;
+
+        let amount_0 = <zenlink_assets::Module<T>>::balance_of(&pair.token_0, &pair.account);
+        if !amount_0.is_zero() {
+            <zenlink_assets::Module<T>>::inner_transfer(&pair.token_0, &pair.account, recipient, amount_0)?;
+        }
+        let amount_1 = <zenlink_assets::Module<T>>::balance_of(&pair.token_1, &pair.account);
+        if !amount_1.is_zero() {
+            <zenlink_assets::Module<T>>::inner_transfer(&pair.token_1, &pair.account, recipient, amount_1)?;
+        }
+
+        <TokensToPair<T>>::remove((pair.token_0, pair.token_1));
+        <Pairs<T>>::remove(pair_id);
+        Self::remove_exchange_records(pair_id, pair.liquidity_id);
+
+        Self::deposit_event(RawEvent::PairClosed(pair_id, recipient.clone(), amount_0, amount_1));
+
+        Ok(())
+    }
+
+    /// Send the currency and token from the exchange account to the receiver, call the
+    /// `FlashSwapHandler`, then check the constant product with the fee after the repayment.
+    /// The exchange is locked during the callback, so it can't touch the exchange.
+    #[transactional]
+    pub fn do_flash_swap(
+        who: &T::AccountId,
+        exchange_id: T::ExchangeId,
+        currency_amount: BalanceOf<T>,
+        token_amount: TokenBalance<T>,
+        receiver: &T::AccountId,
+        data: &[u8],
+    ) -> dispatch::DispatchResult {
+        let mut exchange = Self::get_exchange(exchange_id).ok_or(Error::<T>::ExchangeNotExists)?;
+        Self::ensure_swap_allowed(exchange_id)?;
+        ensure!(!Self::flash_swap_locked(exchange_id), Error::<T>::Locked);
+        ensure!(!currency_amount.is_zero() || !token_amount.is_zero(), Error::<T>::ZeroFlashSwap);
+        ensure!(
+            currency_amount < exchange.currency_reserve && token_amount < exchange.token_reserve,
+            Error::<T>::InsufficientReserve
+        );
+
+        <FlashSwapLocks<T>>::insert(exchange_id, true);
+
+        if !currency_amount.is_zero() {
+            T::Currency::transfer(&exchange.account, receiver, currency_amount, ExistenceRequirement::AllowDeath)?;
+        }
+        if !token_amount.is_zero() {
+            <zenlink_assets::Module<T>>::inner_transfer(&exchange.token_id, &exchange.account, receiver, token_amount)?;
+        }
+
+        T::FlashSwapHandler::on_flash_swap(who, receiver, exchange_id, currency_amount, token_amount, data)?;
+
+        <FlashSwapLocks<T>>::remove(exchange_id);
+
+        let currency_balance = T::Currency::free_balance(&exchange.account);
+        let token_balance = <zenlink_assets::Module<T>>::balance_of(&exchange.token_id, &exchange.account);
+        let currency_repaid = currency_balance.saturating_sub(exchange.currency_reserve - currency_amount);
+        let token_repaid = token_balance.saturating_sub(exchange.token_reserve - token_amount);
+        ensure!(!currency_repaid.is_zero() || !token_repaid.is_zero(), Error::<T>::FlashSwapNotRepaid);
+
+        // The fee is charged on the repaid amounts, as on the input of a swap.
+        let (numerator, denominator) = Self::fee_fraction(Self::fee_rate_of(&exchange));
+        let fee = denominator - numerator;
+        let currency_adjusted = Self::to_u256(currency_balance)? * denominator - Self::to_u256(currency_repaid)? * fee;
+        let token_adjusted = Self::to_u256(token_balance)? * denominator - Self::to_u256(token_repaid)? * fee;
+        let k_adjusted = currency_adjusted.checked_mul(token_adjusted).ok_or(Error::<T>::Overflow)?;
+        let k_required = Self::get_k(&exchange)?
+            .checked_mul(denominator * denominator)
+            .ok_or(Error::<T>::Overflow)?;
+        ensure!(k_adjusted >= k_required, Error::<T>::FlashSwapNotRepaid);
+
+        exchange.currency_reserve = currency_balance;
+        exchange.token_reserve = token_balance;
+        Self::put_exchange(exchange_id, exchange)?;
+
+        Self::deposit_event(RawEvent::FlashSwapped(
+            exchange_id,
+            who.clone(),
+            receiver.clone(),
+            currency_amount,
+            token_amount,
+            currency_repaid,
+            token_repaid,
+        ));
+
+        Ok(())
+    }
+
+    /// Swap Currency to Token.
+    /// Return Amount of Token bought.
+    pub fn get_currency_to_token_input_price(
+        exchange: &ExchangeOf<T>,
+        currency_sold: BalanceOf<T>,
+    ) -> Result<TokenBalance<T>, Error<T>> {
+        if currency_sold == Zero::zero() {
+            return Ok(Zero::zero());
+        }
+
+        let token_reserve = Self::get_token_reserve(exchange);
+        let currency_reserve = Self::get_currency_reserve(exchange);
+        Self::get_input_price(
+            Self::to_u256(currency_sold)?,
+            Self::to_u256(currency_reserve)?,
+            Self::to_u256(token_reserve)?,
+            Self::fee_rate_of(exchange),
+        )
+        .and_then(Self::from_u256)
+    }
+
+    /// Swap Currency to Token.
+    /// Return Amount of Currency sold.
+    pub fn get_currency_to_token_output_price(
+        exchange: &ExchangeOf<T>,
+        tokens_bought: TokenBalance<T>,
+    ) -> Result<BalanceOf<T>, Error<T>> {
+        if tokens_bought == Zero::zero() {
+            return Ok(Zero::zero());
+        }
+
+        let token_reserve = Self::get_token_reserve(exchange);
+        let currency_reserve = Self::get_currency_reserve(exchange);
+        Self::get_output_price(
+            Self::to_u256(tokens_bought)?,
+            Self::to_u256(currency_reserve)?,
+            Self::to_u256(token_reserve)?,
+            Self::fee_rate_of(exchange),
+        )
+        .and_then(Self::from_u256)
+    }
+
+    /// Swap Token to Currency.
+    /// Return Amount of Currency bought.
+    pub fn get_token_to_currency_input_price(
+        exchange: &ExchangeOf<T>,
+        token_sold: TokenBalance<T>,
+    ) -> Result<BalanceOf<T>, Error<T>> {
+        if token_sold == Zero::zero() {
+            return Ok(Zero::zero());
+        }
+
+        let token_reserve = Self::get_token_reserve(exchange);
+        let currency_reserve = Self::get_currency_reserve(exchange);
+        Self::get_input_price(
+            Self::to_u256(token_sold)?,
+            Self::to_u256(token_reserve)?,
+            Self::to_u256(currency_reserve)?,
+            Self::fee_rate_of(exchange),
+        )
+        .and_then(Self::from_u256)
+    }
+
+    /// Swap Token to Currency.
+    /// Return Amount of Token bought.
+    pub fn get_token_to_currency_output_price(
+        exchange: &ExchangeOf<T>,
+        currency_bought: BalanceOf<T>,
+    ) -> Result<TokenBalance<T>, Error<T>> {
+        if currency_bought == Zero::zero() {
+            return Ok(Zero::zero());
+        }
+
+        let token_reserve = Self::get_token_reserve(exchange);
+        let currency_reserve = Self::get_currency_reserve(exchange);
+        Self::get_output_price(
+            Self::to_u256(currency_bought)?,
+            Self::to_u256(token_reserve)?,
+            Self::to_u256(currency_reserve)?,
+            Self::fee_rate_of(exchange),
+        )
+        .and_then(Self::from_u256)
+    }
+
+    /// Get the pair id of two tokens in any order.
+    pub fn get_pair_id(token_a: T::AssetId, token_b: T::AssetId) -> Option<T::ExchangeId> {
+        Self::tokens_to_pair(Self::sort_tokens(token_a, token_b))
+    }
+
+    /// Swap Token to other Token in the pair.
+    /// Return Amount of other Token bought.
+    pub fn get_pair_token_to_token_input_price(
+        pair: &PairOf<T>,
+        token_id: T::AssetId,
+        token_sold: TokenBalance<T>,
+    ) -> Result<TokenBalance<T>, Error<T>> {
+        if token_sold == Zero::zero() {
+            return Ok(Zero::zero());
+        }
+
+        let token_reserve = Self::get_pair_reserve(pair, token_id);
+        let other_token_reserve = Self::get_pair_reserve(pair, Self::other_token_of(pair, token_id));
+        Self::get_input_price(
+            Self::to_u256(token_sold)?,
+            Self::to_u256(token_reserve)?,
+            Self::to_u256(other_token_reserve)?,
+            Self::pair_fee_rate_of(pair),
+        )
+        .and_then(Self::from_u256)
+    }
+
+    /// Swap Token to other Token in the pair.
+    /// Return Amount of Token sold.
+    pub fn get_pair_token_to_token_output_price(
+        pair: &PairOf<T>,
+        token_id: T::AssetId,
+        other_token_bought: TokenBalance<T>,
+    ) -> Result<TokenBalance<T>, Error<T>> {
+        if other_token_bought == Zero::zero() {
+            return Ok(Zero::zero());
+        }
+
+        let token_reserve = Self::get_pair_reserve(pair, token_id);
+        let other_token_reserve = Self::get_pair_reserve(pair, Self::other_token_of(pair, token_id));
+        Self::get_output_price(
+            Self::to_u256(other_token_bought)?,
+            Self::to_u256(token_reserve)?,
+            Self::to_u256(other_token_reserve)?,
+            Self::pair_fee_rate_of(pair),
+        )
+        .and_then(Self::from_u256)
+    }
+
+    /// Get the fee rate charged by the pair.
+    /// Return the pair's own fee rate or the default `ExchangeFeeRate`.
+    pub fn pair_fee_rate_of(pair: &PairOf<T>) -> Permill {
+        pair.fee_rate.unwrap_or_else(T::ExchangeFeeRate::get)
+    }
+
+    /// Get the fee rate charged by the exchange.
+    /// Return the exchange's own fee rate or the default `ExchangeFeeRate`.
+    pub fn fee_rate_of(exchange: &ExchangeOf<T>) -> Permill {
+        exchange.fee_rate.unwrap_or_else(T::ExchangeFeeRate::get)
+    }
+
+    /// Pricing function for converting between Currency and Token.
+    /// Return Amount of Currency or Token bought.
+    fn get_input_price(
+        input_amount: U256,
+        input_reserve: U256,
+        output_reserve: U256,
+        fee_rate: Permill,
+    ) -> Result<U256, Error<T>> {
+        let (fee_numerator, fee_denominator) = Self::fee_fraction(fee_rate);
+        let input_amount_with_fee = input_amount
+            .checked_mul(fee_numerator)
+            .ok_or(Error::<T>::Overflow)?;
+        let denominator = input_reserve
+            .checked_mul(fee_denominator)
+            .and_then(|n| n.checked_add(input_amount_with_fee))
+            .ok_or(Error::<T>::Overflow)?;
+        Self::mul_div(input_amount_with_fee, output_reserve, denominator)
+    }
+
+    /// Pricing function for converting between Currency and Token.
+    /// Return Amount of Currency or Token sold.
     fn get_output_price(
-        output_amount: TokenBalance<T>,
-        input_reserve: TokenBalance<T>,
-        output_reserve: TokenBalance<T>,
-    ) -> TokenBalance<T> {
-        let numerator = input_reserve * output_amount * 1000.into();
-        let denominator = (output_reserve - output_amount) * 997.into();
-        numerator / denominator + 1.into()
+        output_amount: U256,
+        input_reserve: U256,
+        output_reserve: U256,
+        fee_rate: Permill,
+    ) -> Result<U256, Error<T>> {
+        ensure!(output_amount < output_reserve, Error::<T>::InsufficientReserve);
+
+        let (fee_numerator, fee_denominator) = Self::fee_fraction(fee_rate);
+        let numerator = input_reserve
+            .checked_mul(output_amount)
+            .and_then(|n| n.checked_mul(fee_denominator))
+            .ok_or(Error::<T>::Overflow)?;
+        let denominator = (output_reserve - output_amount)
+            .checked_mul(fee_numerator)
+            .ok_or(Error::<T>::Overflow)?;
+        numerator
+            .checked_div(denominator)
+            .ok_or(Error::<T>::NoLiquidity)?
+            .checked_add(U256::one())
+            .ok_or(Error::<T>::Overflow)
+    }
+
+    /// Split the fee rate into the fraction of input kept after fee.
+    /// e.g. 0.3% fee rate is (997_000, 1_000_000)
+    fn fee_fraction(fee_rate: Permill) -> (U256, U256) {
+        let denominator = Permill::one().deconstruct();
+        let numerator = denominator - fee_rate.deconstruct();
+        (U256::from(numerator), U256::from(denominator))
+    }
+
+    /// Calculate `a * b / c` in 256 bits.
+    fn mul_div(a: U256, b: U256, c: U256) -> Result<U256, Error<T>> {
+        a.checked_mul(b)
+            .ok_or(Error::<T>::Overflow)?
+            .checked_div(c)
+            .ok_or(Error::<T>::NoLiquidity)
     }
 
-    /// Convert BalanceOf to TokenBalance
-    /// e.g. BalanceOf is u128, TokenBalance is u64
-    fn convert(balance_of: BalanceOf<T>) -> TokenBalance<T> {
-        let m = balance_of.saturated_into::<u64>();
-        m.saturated_into()
+    /// Integer square root in 256 bits by the babylonian method.
+    fn sqrt(y: U256) -> U256 {
+        if y > U256::from(3u8) {
+            let mut z = y;
+            let mut x = y / U256::from(2u8) + U256::one();
+            while x < z {
+                z = x;
+                x = (y / x + x) / U256::from(2u8);
+            }
+            z
+        } else if !y.is_zero() {
+            U256::one()
+        } else {
+            U256::zero()
+        }
+    }
+
+    /// The status applying to the exchange or pair, the stricter one of its own and the global status.
+    pub fn effective_status(exchange_id: T::ExchangeId) -> ExchangeStatus {
+        Self::exchange_status(exchange_id).max(Self::global_status())
+    }
+
+    fn ensure_swap_allowed(exchange_id: T::ExchangeId) -> Result<(), Error<T>> {
+        match Self::effective_status(exchange_id) {
+            ExchangeStatus::Active => Ok(()),
+            ExchangeStatus::SwapsPaused => Err(Error::<T>::SwapsPaused),
+            ExchangeStatus::WithdrawOnly => Err(Error::<T>::WithdrawOnly),
+        }
+    }
+
+    fn ensure_add_liquidity_allowed(exchange_id: T::ExchangeId) -> Result<(), Error<T>> {
+        ensure!(Self::effective_status(exchange_id) != ExchangeStatus::WithdrawOnly, Error::<T>::WithdrawOnly);
+        Ok(())
+    }
+
+    /// Get the time-weighted average prices of the exchange or pair over at least the last `window` blocks.
+    /// Return the asset 0 price in asset 1 and the asset 1 price in asset 0, the asset 0 is the currency of
+    /// an exchange or the token_0 of a pair.
+    pub fn consult(
+        exchange_id: T::ExchangeId,
+        window: T::BlockNumber,
+    ) -> Result<(FixedU128, FixedU128), Error<T>> {
+        ensure!(!window.is_zero(), Error::<T>::InvalidWindow);
+
+        let now = frame_system::Module::<T>::block_number();
+        let (reserve_0, reserve_1) = Self::get_reserves(exchange_id)?;
+        // The cumulative prices as if they were accumulated now.
+        let current = Self::accumulate_price(Self::price_cumulative(exchange_id), reserve_0, reserve_1, now)?;
+
+        let target = now.saturating_sub(window);
+        let observation = Self::price_observations(exchange_id)
+            .into_iter()
+            .rev()
+            .find(|observation| observation.block_last <= target)
+            .ok_or(Error::<T>::NoPriceObservation)?;
+
+        let elapsed = Self::to_u256(now - observation.block_last)?;
+        // The cumulative prices are wrapping, only their differences are meaningful.
+        let price_0 = current.price_0_cumulative.overflowing_sub(observation.price_0_cumulative).0 / elapsed;
+        let price_1 = current.price_1_cumulative.overflowing_sub(observation.price_1_cumulative).0 / elapsed;
+
+        Ok((
+            FixedU128::from_inner(Self::from_u256(price_0)?),
+            FixedU128::from_inner(Self::from_u256(price_1)?),
+        ))
+    }
+
+    /// The account locking the `MinimumLiquidity` of every exchange and pair, nobody could spend from it.
+    pub fn minimum_liquidity_account() -> T::AccountId {
+        T::ModuleId::get().into_sub_account(b"min_liquidity")
+    }
+
+    /// Deduct the `MinimumLiquidity` from the liquidity created by the first deposit.
+    /// Return the liquidity left to the provider.
+    fn deduct_minimum_liquidity(initial_liquidity: TokenBalance<T>) -> Result<TokenBalance<T>, Error<T>> {
+        let minimum_liquidity = T::MinimumLiquidity::get();
+        ensure!(initial_liquidity > minimum_liquidity, Error::<T>::BelowMinimumLiquidity);
+        Ok(initial_liquidity - minimum_liquidity)
+    }
+
+    /// Mint the `MinimumLiquidity` to the unspendable account on the first deposit.
+    fn lock_minimum_liquidity(liquidity_id: T::AssetId) -> dispatch::DispatchResult {
+        let minimum_liquidity = T::MinimumLiquidity::get();
+        if !minimum_liquidity.is_zero() {
+            <zenlink_assets::Module<T>>::inner_mint(&liquidity_id, &Self::minimum_liquidity_account(), minimum_liquidity)?;
+        }
+
+        Ok(())
+    }
+
+    /// Calculate the protocol fee which is equivalent to 1/6th of the growth in sqrt(k)
+    /// since the last liquidity event.
+    /// Return the `FeeTo` account and the liquidity to mint, `None` if the protocol fee is off.
+    fn calculate_protocol_fee(
+        exchange_id: T::ExchangeId,
+        liquidity_id: T::AssetId,
+        k: U256,
+    ) -> Result<Option<(T::AccountId, TokenBalance<T>)>, Error<T>> {
+        let fee_to = match Self::fee_to() {
+            Some(fee_to) => fee_to,
+            None => return Ok(None),
+        };
+
+        let k_last = Self::k_last(exchange_id);
+        if k_last.is_zero() {
+            return Ok(Some((fee_to, Zero::zero())));
+        }
+
+        let root_k = Self::sqrt(k);
+        let root_k_last = Self::sqrt(k_last);
+        if root_k <= root_k_last {
+            return Ok(Some((fee_to, Zero::zero())));
+        }
+
+        let total_liquidity = Self::to_u256(<zenlink_assets::Module<T>>::total_supply(&liquidity_id))?;
+        let denominator = root_k
+            .checked_mul(U256::from(5u8))
+            .and_then(|n| n.checked_add(root_k_last))
+            .ok_or(Error::<T>::Overflow)?;
+        let fee_liquidity = Self::mul_div(total_liquidity, root_k - root_k_last, denominator)?;
+        Ok(Some((fee_to, Self::from_u256(fee_liquidity)?)))
+    }
+
+    /// Get the total supply of liquidity token including the protocol fee to be minted.
+    fn total_liquidity_with_fee(
+        liquidity_id: T::AssetId,
+        protocol_fee: &Option<(T::AccountId, TokenBalance<T>)>,
+    ) -> Result<TokenBalance<T>, Error<T>> {
+        let total_liquidity = <zenlink_assets::Module<T>>::total_supply(&liquidity_id);
+        match protocol_fee {
+            Some((_, fee_liquidity)) => total_liquidity.checked_add(fee_liquidity).ok_or(Error::<T>::Overflow),
+            None => Ok(total_liquidity),
+        }
+    }
+
+    /// Mint the protocol fee in liquidity token to the `FeeTo` account.
+    fn mint_protocol_fee(
+        exchange_id: T::ExchangeId,
+        liquidity_id: T::AssetId,
+        protocol_fee: &Option<(T::AccountId, TokenBalance<T>)>,
+    ) -> dispatch::DispatchResult {
+        if let Some((fee_to, fee_liquidity)) = protocol_fee {
+            if !fee_liquidity.is_zero() {
+                <zenlink_assets::Module<T>>::inner_mint(&liquidity_id, fee_to, *fee_liquidity)?;
+
+                Self::deposit_event(RawEvent::ProtocolFeeMinted(exchange_id, fee_to.clone(), *fee_liquidity));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Record the reserves product(k) after the liquidity event if the protocol fee is on.
+    fn update_k_last(
+        exchange_id: T::ExchangeId,
+        k: U256,
+        protocol_fee: &Option<(T::AccountId, TokenBalance<T>)>,
+    ) {
+        if protocol_fee.is_some() {
+            <KLast<T>>::insert(exchange_id, k);
+        } else if <KLast<T>>::contains_key(exchange_id) {
+            <KLast<T>>::remove(exchange_id);
+        }
+    }
+
+    /// Get the reserves product(k) of the exchange liquidity pool
+    fn get_k(exchange: &ExchangeOf<T>) -> Result<U256, Error<T>> {
+        Self::to_u256(Self::get_currency_reserve(exchange))?
+            .checked_mul(Self::to_u256(Self::get_token_reserve(exchange))?)
+            .ok_or(Error::<T>::Overflow)
+    }
+
+    /// Get the reserves product(k) of the pair liquidity pool
+    fn get_pair_k(pair: &PairOf<T>) -> Result<U256, Error<T>> {
+        Self::to_u256(Self::get_pair_reserve(pair, pair.token_0))?
+            .checked_mul(Self::to_u256(Self::get_pair_reserve(pair, pair.token_1))?)
+            .ok_or(Error::<T>::Overflow)
+    }
+
+    /// Convert BalanceOf or TokenBalance to U256 for the exchange math
+    fn to_u256<B: TryInto<u128>>(balance: B) -> Result<U256, Error<T>> {
+        balance.try_into().map(U256::from).map_err(|_| Error::<T>::Overflow)
+    }
+
+    /// Convert U256 back to BalanceOf or TokenBalance
+    /// Return `Overflow` error if the value doesn't fit in, instead of saturating it.
+    fn from_u256<B: TryFrom<u128>>(value: U256) -> Result<B, Error<T>> {
+        ensure!(value <= U256::from(u128::max_value()), Error::<T>::Overflow);
+        B::try_from(value.low_u128()).map_err(|_| Error::<T>::Overflow)
+    }
+
+    /// Get the token reserve of the exchange liquidity pool
+    fn get_token_reserve(exchange: &ExchangeOf<T>) -> TokenBalance<T> {
+        exchange.token_reserve
+    }
+
+    /// Resolve the exchanges and pairs of the path into hops starting from `asset_in`.
+    /// Return `InvalidPath` error if the path is empty, repeated or not connected.
+    fn get_swap_hops(
+        asset_in: SwapAssetOf<T>,
+        path: &[SwapHandlerOf<T>],
+    ) -> Result<Vec<SwapHop<T>>, Error<T>> {
+        ensure!(!path.is_empty(), Error::<T>::InvalidPath);
+
+        let mut hops: Vec<SwapHop<T>> = Vec::with_capacity(path.len());
+        let mut asset = asset_in;
+        for swap_handler in path {
+            let exchange_id = Self::get_exchange_id(swap_handler)?;
+            Self::ensure_swap_allowed(exchange_id)?;
+            // The reserves of a repeated hop would be outdated.
+            ensure!(hops.iter().all(|hop| hop.exchange_id != exchange_id), Error::<T>::InvalidPath);
+
+            let hop = Self::get_swap_hop(exchange_id, asset)?;
+            asset = hop.asset_out;
+            hops.push(hop);
+        }
+
+        Ok(hops)
+    }
+
+    /// Resolve the exchange or pair which sells `asset_in` into a hop.
+    fn get_swap_hop(exchange_id: T::ExchangeId, asset_in: SwapAssetOf<T>) -> Result<SwapHop<T>, Error<T>> {
+        if let Some(exchange) = Self::get_exchange(exchange_id) {
+            let currency_reserve = Self::to_u256(Self::get_currency_reserve(&exchange))?;
+            let token_reserve = Self::to_u256(Self::get_token_reserve(&exchange))?;
+            let fee_rate = Self::fee_rate_of(&exchange);
+
+            match asset_in {
+                SwapAsset::Currency => Ok(SwapHop {
+                    exchange_id,
+                    account: exchange.account,
+                    asset_in,
+                    asset_out: SwapAsset::Token(exchange.token_id),
+                    reserve_in: currency_reserve,
+                    reserve_out: token_reserve,
+                    fee_rate,
+                }),
+                SwapAsset::Token(token_id) if token_id == exchange.token_id => Ok(SwapHop {
+                    exchange_id,
+                    account: exchange.account,
+                    asset_in,
+                    asset_out: SwapAsset::Currency,
+                    reserve_in: token_reserve,
+                    reserve_out: currency_reserve,
+                    fee_rate,
+                }),
+                _ => Err(Error::<T>::InvalidPath),
+            }
+        } else if let Some(pair) = Self::get_pair(exchange_id) {
+            match asset_in {
+                SwapAsset::Token(token_id) if token_id == pair.token_0 || token_id == pair.token_1 => {
+                    let other_token_id = Self::other_token_of(&pair, token_id);
+                    Ok(SwapHop {
+                        exchange_id,
+                        reserve_in: Self::to_u256(Self::get_pair_reserve(&pair, token_id))?,
+                        reserve_out: Self::to_u256(Self::get_pair_reserve(&pair, other_token_id))?,
+                        fee_rate: Self::pair_fee_rate_of(&pair),
+                        account: pair.account,
+                        asset_in,
+                        asset_out: SwapAsset::Token(other_token_id),
+                    })
+                }
+                _ => Err(Error::<T>::InvalidPath),
+            }
+        } else {
+            Err(Error::<T>::ExchangeNotExists)
+        }
+    }
+
+    /// Get the amounts of every hop by the exact input amount.
+    fn get_amounts_out(hops: &[SwapHop<T>], amount_in: U256) -> Result<Vec<U256>, Error<T>> {
+        let mut amounts = Vec::with_capacity(hops.len() + 1);
+        let mut amount = amount_in;
+        amounts.push(amount);
+        for hop in hops {
+            amount = Self::get_input_price(amount, hop.reserve_in, hop.reserve_out, hop.fee_rate)?;
+            amounts.push(amount);
+        }
+
+        Ok(amounts)
+    }
+
+    /// Get the amounts of every hop by the exact output amount.
+    fn get_amounts_in(hops: &[SwapHop<T>], amount_out: U256) -> Result<Vec<U256>, Error<T>> {
+        let mut amounts = Vec::with_capacity(hops.len() + 1);
+        let mut amount = amount_out;
+        amounts.push(amount);
+        for hop in hops.iter().rev() {
+            amount = Self::get_output_price(amount, hop.reserve_in, hop.reserve_out, hop.fee_rate)?;
+            amounts.push(amount);
+        }
+        amounts.reverse();
+
+        Ok(amounts)
+    }
+
+    /// Move the assets along the hops, `amounts[i]` is sold in `hops[i]`.
+    fn swap_by_path(
+        buyer: &T::AccountId,
+        hops: &[SwapHop<T>],
+        amounts: &[U256],
+        recipient: &T::AccountId,
+    ) -> dispatch::DispatchResult {
+        let first_hop = &hops[0];
+        match first_hop.asset_in {
+            SwapAsset::Currency => {
+                T::Currency::transfer(buyer, &first_hop.account, Self::from_u256(amounts[0])?, ExistenceRequirement::KeepAlive)?;
+            }
+            SwapAsset::Token(token_id) => {
+                <zenlink_assets::Module<T>>::inner_transfer(&token_id, buyer, &first_hop.account, Self::from_u256(amounts[0])?)?;
+            }
+        }
+
+        for (i, hop) in hops.iter().enumerate() {
+            match hops.get(i + 1) {
+                Some(next_hop) => Self::transfer_swap_asset(hop.asset_out, &hop.account, &next_hop.account, amounts[i + 1], ExistenceRequirement::KeepAlive)?,
+                None => Self::transfer_swap_asset(hop.asset_out, &hop.account, recipient, amounts[i + 1], ExistenceRequirement::AllowDeath)?,
+            }
+
+            let amount_sold: AssetBalance = Self::from_u256(amounts[i])?;
+            let amount_bought: AssetBalance = Self::from_u256(amounts[i + 1])?;
+            Self::update_hop_reserves(hop, amounts[i], amounts[i + 1])?;
+
+            Self::deposit_event(RawEvent::PathHopSwapped(hop.exchange_id, hop.asset_in, amount_sold, hop.asset_out, amount_bought));
+        }
+
+        Ok(())
+    }
+
+    /// Store the exchange after its reserves changed, the prices are accumulated with the
+    /// reserves before the first change in each block.
+    fn put_exchange(exchange_id: T::ExchangeId, exchange: ExchangeOf<T>) -> Result<(), Error<T>> {
+        if let Some(old_exchange) = Self::get_exchange(exchange_id) {
+            Self::update_price_cumulative(
+                exchange_id,
+                Self::to_u256(old_exchange.currency_reserve)?,
+                Self::to_u256(old_exchange.token_reserve)?,
+            )?;
+        }
+        <Exchanges<T>>::insert(exchange_id, exchange);
+
+        Ok(())
+    }
+
+    /// Store the pair after its reserves changed, the prices are accumulated with the
+    /// reserves before the first change in each block.
+    fn put_pair(pair_id: T::ExchangeId, pair: PairOf<T>) -> Result<(), Error<T>> {
+        if let Some(old_pair) = Self::get_pair(pair_id) {
+            Self::update_price_cumulative(
+                pair_id,
+                Self::to_u256(old_pair.reserve_0)?,
+                Self::to_u256(old_pair.reserve_1)?,
+            )?;
+        }
+        <Pairs<T>>::insert(pair_id, pair);
+
+        Ok(())
+    }
+
+    /// The number of price observations kept for every exchange and pair.
+    fn max_price_observations() -> u32 {
+        T::MaxPriceObservations::get().min(MAX_PRICE_OBSERVATIONS)
+    }
+
+    /// Accumulate the prices once per block and keep the result as an observation.
+    fn update_price_cumulative(exchange_id: T::ExchangeId, reserve_0: U256, reserve_1: U256) -> Result<(), Error<T>> {
+        let now = frame_system::Module::<T>::block_number();
+        let cumulative = Self::price_cumulative(exchange_id);
+        if now <= cumulative.block_last {
+            return Ok(());
+        }
+
+        let cumulative = Self::accumulate_price(cumulative, reserve_0, reserve_1, now)?;
+        <PriceCumulatives<T>>::insert(exchange_id, &cumulative);
+
+        let max_observations = Self::max_price_observations() as usize;
+        if max_observations > 0 {
+            <PriceObservations<T>>::mutate(exchange_id, |observations| {
+                if observations.len() >= max_observations {
+                    observations.drain(..=observations.len() - max_observations);
+                }
+                observations.push(cumulative);
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Add the prices of the reserves, weighted by the blocks elapsed, to the cumulative prices.
+    fn accumulate_price(
+        mut cumulative: PriceCumulative<T::BlockNumber>,
+        reserve_0: U256,
+        reserve_1: U256,
+        now: T::BlockNumber,
+    ) -> Result<PriceCumulative<T::BlockNumber>, Error<T>> {
+        if now > cumulative.block_last && !reserve_0.is_zero() && !reserve_1.is_zero() {
+            let elapsed = Self::to_u256(now - cumulative.block_last)?;
+            let accuracy = U256::from(FixedU128::accuracy());
+            let price_0 = Self::mul_div(reserve_1, accuracy, reserve_0)?;
+            let price_1 = Self::mul_div(reserve_0, accuracy, reserve_1)?;
+
+            // The cumulative prices are wrapping on purpose, as in Uniswap V2.
+            cumulative.price_0_cumulative = cumulative.price_0_cumulative
+                .overflowing_add(price_0.overflowing_mul(elapsed).0).0;
+            cumulative.price_1_cumulative = cumulative.price_1_cumulative
+                .overflowing_add(price_1.overflowing_mul(elapsed).0).0;
+        }
+        cumulative.block_last = now;
+
+        Ok(cumulative)
+    }
+
+    /// Get the reserves of the asset 0 and asset 1 of the exchange or pair.
+    fn get_reserves(exchange_id: T::ExchangeId) -> Result<(U256, U256), Error<T>> {
+        if let Some(exchange) = Self::get_exchange(exchange_id) {
+            Ok((Self::to_u256(exchange.currency_reserve)?, Self::to_u256(exchange.token_reserve)?))
+        } else if let Some(pair) = Self::get_pair(exchange_id) {
+            Ok((Self::to_u256(pair.reserve_0)?, Self::to_u256(pair.reserve_1)?))
+        } else {
+            Err(Error::<T>::ExchangeNotExists)
+        }
+    }
+
+    /// Record the amount sold into and the amount bought out of the hop's reserves.
+    fn update_hop_reserves(hop: &SwapHop<T>, amount_in: U256, amount_out: U256) -> Result<(), Error<T>> {
+        if let Some(mut exchange) = Self::get_exchange(hop.exchange_id) {
+            if hop.asset_in == SwapAsset::Currency {
+                exchange.currency_reserve = Self::add_reserve(exchange.currency_reserve, Self::from_u256(amount_in)?)?;
+                exchange.token_reserve = Self::sub_reserve(exchange.token_reserve, Self::from_u256(amount_out)?)?;
+            } else {
+                exchange.token_reserve = Self::add_reserve(exchange.token_reserve, Self::from_u256(amount_in)?)?;
+                exchange.currency_reserve = Self::sub_reserve(exchange.currency_reserve, Self::from_u256(amount_out)?)?;
+            }
+            Self::put_exchange(hop.exchange_id, exchange)?;
+        } else if let Some(mut pair) = Self::get_pair(hop.exchange_id) {
+            if let (SwapAsset::Token(token_in), SwapAsset::Token(token_out)) = (hop.asset_in, hop.asset_out) {
+                let reserve = Self::pair_reserve_mut(&mut pair, token_in);
+                *reserve = Self::add_reserve(*reserve, Self::from_u256(amount_in)?)?;
+                let reserve = Self::pair_reserve_mut(&mut pair, token_out);
+                *reserve = Self::sub_reserve(*reserve, Self::from_u256(amount_out)?)?;
+            }
+            Self::put_pair(hop.exchange_id, pair)?;
+        }
+
+        Ok(())
+    }
+
+    /// Transfer the native currency or token.
+    fn transfer_swap_asset(
+        asset: SwapAssetOf<T>,
+        from: &T::AccountId,
+        to: &T::AccountId,
+        amount: U256,
+        existence_requirement: ExistenceRequirement,
+    ) -> dispatch::DispatchResult {
+        match asset {
+            SwapAsset::Currency => T::Currency::transfer(from, to, Self::from_u256(amount)?, existence_requirement),
+            SwapAsset::Token(token_id) => <zenlink_assets::Module<T>>::inner_transfer(&token_id, from, to, Self::from_u256(amount)?),
+        }
+    }
+
+    /// The zero amount error of the native currency or token.
+    fn zero_amount_error(asset: SwapAssetOf<T>) -> Error<T> {
+        match asset {
+            SwapAsset::Currency => Error::<T>::ZeroCurrency,
+            SwapAsset::Token(_) => Error::<T>::ZeroToken,
+        }
+    }
+
+    /// Get the pair_id and pair by two tokens in any order.
+    fn get_pair_by_tokens(
+        token_a: T::AssetId,
+        token_b: T::AssetId,
+    ) -> Result<(T::ExchangeId, PairOf<T>), Error<T>> {
+        Self::get_pair_id(token_a, token_b)
+            .and_then(|pair_id| Self::get_pair(pair_id).map(|pair| (pair_id, pair)))
+            .ok_or(Error::<T>::PairNotExists)
+    }
+
+    /// Sort two tokens by asset id, the smaller one is the first.
+    fn sort_tokens(token_a: T::AssetId, token_b: T::AssetId) -> (T::AssetId, T::AssetId) {
+        if token_a < token_b {
+            (token_a, token_b)
+        } else {
+            (token_b, token_a)
+        }
+    }
+
+    /// Get the other token of the pair.
+    fn other_token_of(pair: &PairOf<T>, token_id: T::AssetId) -> T::AssetId {
+        if token_id == pair.token_0 {
+            pair.token_1
+        } else {
+            pair.token_0
+        }
+    }
+
+    /// Get the token reserve of the pair liquidity pool
+    fn get_pair_reserve(pair: &PairOf<T>, token_id: T::AssetId) -> TokenBalance<T> {
+        if token_id == pair.token_0 {
+            pair.reserve_0
+        } else {
+            pair.reserve_1
+        }
+    }
+
+    /// Get the mutable token reserve of the pair liquidity pool
+    fn pair_reserve_mut(pair: &mut PairOf<T>, token_id: T::AssetId) -> &mut TokenBalance<T> {
+        if token_id == pair.token_0 {
+            &mut pair.reserve_0
+        } else {
+            &mut pair.reserve_1
+        }
     }
 
-    /// Convert TokenBalance to BalanceOf
-    /// e.g. BalanceOf is u128, TokenBalance is u64
-    fn unconvert(token_balance: TokenBalance<T>) -> BalanceOf<T> {
-        let m = token_balance.saturated_into::<u64>();
-        m.saturated_into()
+    /// Get the currency reserve of the exchange liquidity pool
+    fn get_currency_reserve(exchange: &ExchangeOf<T>) -> BalanceOf<T> {
+        exchange.currency_reserve
     }
 
-    /// Get the token balance of the exchange liquidity pool
-    fn get_token_reserve(exchange: &Exchange<T::AccountId, T::AssetId>) -> TokenBalance<T> {
-        <zenlink_assets::Module<T>>::balance_of(&exchange.token_id, &exchange.account)
+    /// Add the amount deposited to the exchange or pair into the reserve.
+    fn add_reserve<B: CheckedAdd>(reserve: B, amount: B) -> Result<B, Error<T>> {
+        reserve.checked_add(&amount).ok_or(Error::<T>::Overflow)
     }
 
-    /// Get the currency balance of the exchange liquidity pool
-    fn get_currency_reserve(exchange: &Exchange<T::AccountId, T::AssetId>) -> BalanceOf<T> {
-        T::Currency::free_balance(&exchange.account)
+    /// Take the amount withdrawn from the exchange or pair out of the reserve.
+    fn sub_reserve<B: CheckedSub>(reserve: B, amount: B) -> Result<B, Error<T>> {
+        reserve.checked_sub(&amount).ok_or(Error::<T>::InsufficientReserve)
     }
 }
@@ -0,0 +1,77 @@
+//! Storage migrations of the dex module.
+
+use frame_support::{
+    storage::migration::{get_storage_value, take_storage_value},
+    weights::Weight,
+    Blake2_128Concat, Blake2_256, StorageHasher,
+};
+
+use super::*;
+
+const MODULE: &[u8] = b"DexStorage";
+const ASSETS_MODULE: &[u8] = b"Assets";
+
+/// The exchange of `Releases::V1`, without the fee rate and the reserves.
+#[derive(Encode, Decode)]
+pub(crate) struct OldExchange<AccountId, AssetId> {
+    pub token_id: AssetId,
+    pub liquidity_id: AssetId,
+    pub account: AccountId,
+}
+
+/// Take the value of the map hashed by `opaque_blake2_256`.
+fn take_opaque<K: Encode, V: Decode>(item: &[u8], key: K) -> Option<V> {
+    take_storage_value(MODULE, item, &Blake2_256::hash(&key.encode()))
+}
+
+/// The token balance of the account, whether the assets module has moved the balances keyed by
+/// the `(AssetId, AccountId)` tuples to the double map or not, so the modules migrate in any order.
+fn token_balance_of<T: Trait>(token_id: &T::AssetId, account: &T::AccountId) -> TokenBalance<T> {
+    get_storage_value(ASSETS_MODULE, b"Balances", &Blake2_128Concat::hash(&(token_id, account).encode()))
+        .unwrap_or_else(|| <zenlink_assets::Module<T>>::balance_of(token_id, account))
+}
+
+impl<T: Trait> Module<T> {
+    /// Move the maps hashed by `opaque_blake2_256` to `twox_64_concat`, and the exchanges to the
+    /// layout with the fee rate and the reserves. The old keys can't be iterated, they are found
+    /// from the exchange ids below `NextExchangeId` instead.
+    pub(crate) fn migrate_to_v2() -> Weight {
+        let next_exchange_id = Self::next_exchange_id();
+        let mut reads: Weight = 1;
+        let mut writes: Weight = 0;
+
+        let mut exchange_id = T::ExchangeId::zero();
+        while exchange_id < next_exchange_id {
+            reads += 1;
+
+            if let Some(old_exchange) = take_opaque::<_, OldExchange<T::AccountId, T::AssetId>>(b"Exchanges", exchange_id) {
+                reads += 5;
+
+                if let Some(id) = take_opaque::<_, T::ExchangeId>(b"TokenToExchange", old_exchange.token_id) {
+                    <TokenToExchange<T>>::insert(old_exchange.token_id, id);
+                    writes += 2;
+                }
+                if let Some(id) = take_opaque::<_, T::ExchangeId>(b"ZLKToExchange", old_exchange.liquidity_id) {
+                    <ZLKToExchange<T>>::insert(old_exchange.liquidity_id, id);
+                    writes += 2;
+                }
+
+                // The reserves weren't tracked, all the balances of the exchange account are in the pool.
+                let exchange = Exchange {
+                    token_id: old_exchange.token_id,
+                    liquidity_id: old_exchange.liquidity_id,
+                    fee_rate: None,
+                    token_reserve: token_balance_of::<T>(&old_exchange.token_id, &old_exchange.account),
+                    currency_reserve: T::Currency::free_balance(&old_exchange.account),
+                    account: old_exchange.account,
+                };
+                <Exchanges<T>>::insert(exchange_id, exchange);
+                writes += 2;
+            }
+
+            exchange_id += One::one();
+        }
+
+        T::DbWeight::get().reads_writes(reads, writes)
+    }
+}
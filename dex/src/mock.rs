@@ -1,10 +1,18 @@
-use crate::{Module, Trait};
-use frame_support::{impl_outer_origin, parameter_types, weights::Weight};
+use crate::{FlashSwapHandler, Module, SwapHandler, Trait};
+use codec::Decode;
+use frame_support::{
+    dispatch::DispatchResult,
+    impl_outer_origin, parameter_types,
+    traits::{Currency as CurrencyT, ExistenceRequirement, Get},
+    weights::Weight,
+};
+use std::cell::RefCell;
+use frame_system::EnsureRoot;
 use sp_core::H256;
 use sp_runtime::{
     testing::Header,
     traits::{BlakeTwo256, IdentityLookup},
-    ModuleId, Perbill,
+    ModuleId, Perbill, Permill,
 };
 
 pub use zenlink_assets::AssetInfo;
@@ -69,10 +77,74 @@ impl zenlink_assets::Trait for Test {
     type Event = ();
     type TokenBalance = u64;
     type AssetId = u32;
+    type WeightInfo = ();
 }
 
 parameter_types! {
     pub const DEXModuleId: ModuleId = ModuleId(*b"zlk_dex1");
+    pub const ExchangeFeeRate: Permill = Permill::from_parts(3_000);
+    pub const MaxPriceObservations: u32 = 3;
+}
+
+thread_local! {
+    static MINIMUM_LIQUIDITY: RefCell<u64> = RefCell::new(0);
+    static FLASH_SWAP_REENTER: RefCell<bool> = RefCell::new(false);
+}
+
+pub struct MinimumLiquidity;
+
+impl Get<u64> for MinimumLiquidity {
+    fn get() -> u64 {
+        MINIMUM_LIQUIDITY.with(|v| *v.borrow())
+    }
+}
+
+pub fn set_minimum_liquidity(minimum_liquidity: u64) {
+    MINIMUM_LIQUIDITY.with(|v| *v.borrow_mut() = minimum_liquidity);
+}
+
+/// Make the flash swap handler add the repayment as liquidity to the exchange instead.
+pub fn set_flash_swap_reenter(reenter: bool) {
+    FLASH_SWAP_REENTER.with(|v| *v.borrow_mut() = reenter);
+}
+
+/// Repay the flash swap with the `(currency, token)` amounts encoded in the data, nothing if no data.
+pub struct RepayFlashSwap;
+
+impl FlashSwapHandler<u128, u32, u128, u64> for RepayFlashSwap {
+    fn on_flash_swap(
+        _sender: &u128,
+        receiver: &u128,
+        exchange_id: u32,
+        _currency_amount: u128,
+        _token_amount: u64,
+        data: &[u8],
+    ) -> DispatchResult {
+        let (currency_repay, token_repay) = <(u128, u64)>::decode(&mut &data[..]).unwrap_or_default();
+        let exchange = DexModule::get_exchange_info(exchange_id).unwrap();
+
+        if FLASH_SWAP_REENTER.with(|v| *v.borrow()) {
+            return DexModule::add_liquidity(
+                Origin::signed(*receiver),
+                SwapHandler::from_exchange_id(exchange_id),
+                currency_repay,
+                0,
+                token_repay,
+                100,
+            );
+        }
+
+        <Currency as CurrencyT<u128>>::transfer(receiver, &exchange.account, currency_repay, ExistenceRequirement::KeepAlive)?;
+        if token_repay > 0 {
+            TokenModule::inner_transfer(&exchange.token_id, receiver, &exchange.account, token_repay)?;
+        }
+
+        Ok(())
+    }
+
+    fn on_flash_swap_weight(_data: &[u8]) -> Weight {
+        0
+    }
 }
 
 impl Trait for Test {
@@ -80,8 +152,15 @@ impl Trait for Test {
     type ExchangeId = u32;
     type Currency = pallet_balances::Module<Test>;
     type ModuleId = DEXModuleId;
+    type ExchangeFeeRate = ExchangeFeeRate;
+    type ControlOrigin = EnsureRoot<u128>;
+    type MinimumLiquidity = MinimumLiquidity;
+    type MaxPriceObservations = MaxPriceObservations;
+    type WeightInfo = ();
+    type FlashSwapHandler = RepayFlashSwap;
 }
 
+pub type System = frame_system::Module<Test>;
 pub type Currency = pallet_balances::Module<Test>;
 pub type TokenModule = zenlink_assets::Module<Test>;
 pub type DexModule = Module<Test>;
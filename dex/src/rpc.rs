@@ -6,8 +6,9 @@ use std::result::Result as StdResult;
 use codec::{Decode, Encode};
 #[cfg(feature = "std")]
 use serde::{de, Deserialize, ser, Serialize};
-use sp_std::convert::TryInto;
-use sp_std::vec::Vec;
+use frame_support::IterableStorageMap;
+use sp_runtime::Perbill;
+use sp_std::{vec, vec::Vec};
 
 use super::*;
 
@@ -41,12 +42,104 @@ pub mod serde_text {
 #[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
 pub struct ExchangeInfo<AccountId, AssetId, TokenBalance, Balance, ExchangeId> {
     #[cfg_attr(feature = "std", serde(flatten))]
-    pub exchange: Exchange<AccountId, AssetId>,
+    pub exchange: Exchange<AccountId, AssetId, TokenBalance, Balance>,
+    pub exchange_id: ExchangeId,
+}
+
+/// The exchange info of version 1 of the runtime api, the exchange had no fee rate and the
+/// reserves were the balances of the exchange account. It decodes the old `ExchangeInfo`.
+#[derive(PartialEq, Eq, Clone, Default, Encode, Decode, RuntimeDebug)]
+pub struct ExchangeInfoV1<AccountId, AssetId, TokenBalance, Balance, ExchangeId> {
+    pub token_id: AssetId,
+    pub liquidity_id: AssetId,
+    pub account: AccountId,
     pub token_reserve: TokenBalance,
     pub currency_reserve: Balance,
     pub exchange_id: ExchangeId,
 }
 
+impl<AccountId, AssetId, TokenBalance, Balance, ExchangeId> From<ExchangeInfoV1<AccountId, AssetId, TokenBalance, Balance, ExchangeId>>
+    for ExchangeInfo<AccountId, AssetId, TokenBalance, Balance, ExchangeId>
+{
+    /// The exchanges of version 1 charge the default fee rate.
+    fn from(exchange_info: ExchangeInfoV1<AccountId, AssetId, TokenBalance, Balance, ExchangeId>) -> Self {
+        ExchangeInfo {
+            exchange: Exchange {
+                token_id: exchange_info.token_id,
+                liquidity_id: exchange_info.liquidity_id,
+                account: exchange_info.account,
+                fee_rate: None,
+                token_reserve: exchange_info.token_reserve,
+                currency_reserve: exchange_info.currency_reserve,
+            },
+            exchange_id: exchange_info.exchange_id,
+        }
+    }
+}
+
+/// The maximum number of exchanges in a page of the listing.
+pub const MAX_EXCHANGES_PAGE_SIZE: u32 = 100;
+
+/// The filter of the exchange listing, every condition set must match.
+#[derive(PartialEq, Eq, Clone, Default, Encode, Decode, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct ExchangeFilter<AssetId, Balance> {
+    /// The token traded in the exchange.
+    pub token_id: Option<AssetId>,
+    /// The minimum currency reserve of the exchange.
+    pub min_currency_reserve: Option<Balance>,
+}
+
+impl<AssetId: PartialEq, Balance: PartialOrd> ExchangeFilter<AssetId, Balance> {
+    pub fn matches<AccountId, TokenBalance>(&self, exchange: &Exchange<AccountId, AssetId, TokenBalance, Balance>) -> bool {
+        self.token_id.as_ref().map_or(true, |token_id| *token_id == exchange.token_id)
+            && self.min_currency_reserve.as_ref().map_or(true, |reserve| exchange.currency_reserve >= *reserve)
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct ExchangePage<AccountId, AssetId, TokenBalance, Balance, ExchangeId> {
+    pub exchanges: Vec<ExchangeInfo<AccountId, AssetId, TokenBalance, Balance, ExchangeId>>,
+    /// The cursor of the next page, `None` for the last page.
+    pub next_cursor: Option<ExchangeId>,
+    /// The number of all exchanges matching the filter.
+    pub total: u32,
+}
+
+/// Take the page of the exchanges matching the filter, from the cursor on.
+/// The exchanges must be ordered by id, the limit is capped by `MAX_EXCHANGES_PAGE_SIZE`.
+pub fn paginate_exchanges<AccountId, AssetId, TokenBalance, Balance, ExchangeId>(
+    exchanges: Vec<ExchangeInfo<AccountId, AssetId, TokenBalance, Balance, ExchangeId>>,
+    cursor: Option<ExchangeId>,
+    limit: u32,
+    filter: &ExchangeFilter<AssetId, Balance>,
+) -> ExchangePage<AccountId, AssetId, TokenBalance, Balance, ExchangeId>
+    where
+        AssetId: PartialEq,
+        Balance: PartialOrd,
+        ExchangeId: PartialOrd + Copy,
+{
+    let matched = exchanges
+        .into_iter()
+        .filter(|exchange_info| filter.matches(&exchange_info.exchange))
+        .collect::<Vec<_>>();
+    let total = matched.len() as u32;
+
+    let limit = limit.min(MAX_EXCHANGES_PAGE_SIZE) as usize;
+    let mut exchanges = matched
+        .into_iter()
+        .skip_while(|exchange_info| cursor.map_or(false, |cursor| exchange_info.exchange_id < cursor))
+        .take(limit + 1)
+        .collect::<Vec<_>>();
+    let next_cursor = exchanges.get(limit).map(|exchange_info| exchange_info.exchange_id);
+    exchanges.truncate(limit);
+
+    ExchangePage { exchanges, next_cursor, total }
+}
+
 #[derive(PartialEq, Eq, Clone, Default, Encode, Decode, RuntimeDebug)]
 #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
@@ -59,6 +152,58 @@ pub struct TokenInfo<TokenBalance> {
     pub decimals: u8,
 }
 
+#[derive(PartialEq, Eq, Clone, Default, Encode, Decode, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct AmountQuote<TokenBalance> {
+    /// The amount of the output asset bought, or of the input asset sold.
+    pub amount: TokenBalance,
+    /// How far the trade moves from the current price, the fee excluded.
+    pub price_impact: Permill,
+}
+
+/// The maximum number of hops of a route found by `find_best_route`.
+pub const MAX_ROUTE_HOPS: u32 = 4;
+
+/// Which amount of the swap is exact, the other one is optimized by the route.
+#[derive(Encode, Decode, Eq, PartialEq, Clone, Copy, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum SwapKind {
+    /// Sell the exact input amount for the most output.
+    ExactIn,
+    /// Buy the exact output amount for the least input.
+    ExactOut,
+}
+
+#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct SwapRoute<ExchangeId, AssetId, TokenBalance> {
+    /// The exchanges and pairs to swap in by order, the path of `swap_exact_in_by_path`
+    /// and `swap_exact_out_by_path`.
+    pub path: Vec<SwapHandler<ExchangeId, AssetId>>,
+    /// The amounts of every hop, `amounts[i]` is sold in `path[i]` and the last one is bought.
+    pub amounts: Vec<TokenBalance>,
+}
+
+#[derive(PartialEq, Eq, Clone, Default, Encode, Decode, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct LiquidityPosition<AssetId, TokenBalance, Balance, ExchangeId> {
+    pub exchange_id: ExchangeId,
+    pub liquidity_id: AssetId,
+    /// The liquidity token balance of the owner.
+    pub liquidity: TokenBalance,
+    /// The total supply of the liquidity token, with the protocol fee to be minted.
+    pub total_liquidity: TokenBalance,
+    /// The share of the owner in the liquidity pool.
+    pub share: Perbill,
+    /// The currency withdrawn by removing all the liquidity now.
+    pub currency_amount: Balance,
+    /// The token withdrawn by removing all the liquidity now.
+    pub token_amount: TokenBalance,
+}
+
 impl<T: Trait> Module<T> {
     pub fn get_token_info(token_id: T::AssetId) -> Option<TokenInfo<T::TokenBalance>> {
         <zenlink_assets::Module<T>>::asset_info(&token_id)
@@ -97,37 +242,295 @@ impl<T: Trait> Module<T> {
     pub fn get_exchange_by_id(exchange_id: T::ExchangeId) -> Option<ExchangeInfo<T::AccountId, T::AssetId, TokenBalance<T>, BalanceOf<T>, T::ExchangeId>> {
         Self::get_exchange_info(exchange_id).
             map(|exchange| {
-                let token_reserve = Self::get_token_reserve(&exchange);
-                let currency_reserve = Self::get_currency_reserve(&exchange);
-
                 ExchangeInfo {
                     exchange,
-                    token_reserve,
-                    currency_reserve,
                     exchange_id,
                 }
             })
     }
 
-    // TODO：Pagination
+    /// All exchanges ordered by id, `get_exchanges_paged` is preferred for many exchanges.
     pub fn get_exchanges() -> Vec<ExchangeInfo<T::AccountId, T::AssetId, TokenBalance<T>, BalanceOf<T>, T::ExchangeId>> {
-        let exchange_count = Self::next_exchange_id().try_into().unwrap_or_default();
-
-        let mut exchanges = Vec::with_capacity(exchange_count);
-        for exchange_id in 0..exchange_count {
-            if let Some(exchange) = Self::get_exchange_info((exchange_id as u32).into()) {
-                let token_reserve = Self::get_token_reserve(&exchange);
-                let currency_reserve = Self::get_currency_reserve(&exchange);
-                exchanges.push(ExchangeInfo {
-                    exchange,
-                    token_reserve,
-                    currency_reserve,
-                    exchange_id: (exchange_id as u32).into(),
-                })
+        let mut exchanges = <Exchanges<T>>::iter()
+            .map(|(exchange_id, exchange)| ExchangeInfo { exchange, exchange_id })
+            .collect::<Vec<_>>();
+        // The map is iterated in the order of the key hashes.
+        exchanges.sort_by_key(|exchange_info| exchange_info.exchange_id);
+
+        exchanges
+    }
+
+    /// The page of the exchanges matching the filter, from the cursor on, ordered by id.
+    pub fn get_exchanges_paged(
+        cursor: Option<T::ExchangeId>,
+        limit: u32,
+        filter: ExchangeFilter<T::AssetId, BalanceOf<T>>,
+    ) -> ExchangePage<T::AccountId, T::AssetId, TokenBalance<T>, BalanceOf<T>, T::ExchangeId> {
+        paginate_exchanges(Self::get_exchanges(), cursor, limit, &filter)
+    }
+
+    pub fn get_average_price(exchange_id: T::ExchangeId, window: u32) -> Option<(FixedU128, FixedU128)> {
+        Self::consult(exchange_id, window.into()).ok()
+    }
+
+    /// Quote the output amount of selling the exact input amount, token to token trades go
+    /// through the pair of the tokens or the currency of both exchanges, whichever buys more.
+    pub fn get_amount_out(
+        asset_in: SwapAssetOf<T>,
+        asset_out: SwapAssetOf<T>,
+        amount_in: AssetBalance,
+    ) -> Option<AmountQuote<AssetBalance>> {
+        let amount_in = Self::to_u256(amount_in).ok()?;
+        // The fewer hops win the tie.
+        let (hops, amount_out) = Self::get_quote_routes(asset_in, asset_out)
+            .into_iter()
+            .filter_map(|hops| {
+                let amounts = Self::get_amounts_out(&hops, amount_in).ok()?;
+                let amount_out = amounts[amounts.len() - 1];
+                Some((hops, amount_out))
+            })
+            .max_by(|(hops, amount_out), (other_hops, other_amount_out)| {
+                amount_out.cmp(other_amount_out).then(other_hops.len().cmp(&hops.len()))
+            })?;
+
+        let mut spot_amount_out = amount_in;
+        for hop in hops.iter() {
+            let (fee_numerator, fee_denominator) = Self::fee_fraction(hop.fee_rate);
+            spot_amount_out = Self::mul_div(spot_amount_out, hop.reserve_out * fee_numerator, hop.reserve_in * fee_denominator).ok()?;
+        }
+
+        Some(AmountQuote {
+            amount: Self::from_u256(amount_out).ok()?,
+            price_impact: Self::get_price_impact(amount_out, spot_amount_out),
+        })
+    }
+
+    /// Quote the input amount of buying the exact output amount, token to token trades go
+    /// through the pair of the tokens or the currency of both exchanges, whichever costs less.
+    pub fn get_amount_in(
+        asset_in: SwapAssetOf<T>,
+        asset_out: SwapAssetOf<T>,
+        amount_out: AssetBalance,
+    ) -> Option<AmountQuote<AssetBalance>> {
+        let amount_out = Self::to_u256(amount_out).ok()?;
+        // The fewer hops win the tie.
+        let (hops, amount_in) = Self::get_quote_routes(asset_in, asset_out)
+            .into_iter()
+            .filter_map(|hops| {
+                let amount_in = Self::get_amounts_in(&hops, amount_out).ok()?[0];
+                Some((hops, amount_in))
+            })
+            .min_by(|(hops, amount_in), (other_hops, other_amount_in)| {
+                amount_in.cmp(other_amount_in).then(hops.len().cmp(&other_hops.len()))
+            })?;
+
+        let mut spot_amount_in = amount_out;
+        for hop in hops.iter().rev() {
+            let (fee_numerator, fee_denominator) = Self::fee_fraction(hop.fee_rate);
+            spot_amount_in = Self::mul_div(spot_amount_in, hop.reserve_in * fee_denominator, hop.reserve_out * fee_numerator).ok()?;
+        }
+
+        Some(AmountQuote {
+            amount: Self::from_u256(amount_in).ok()?,
+            price_impact: Self::get_price_impact(spot_amount_in, amount_in),
+        })
+    }
+
+    /// Resolve the routes trading the assets, the exchange of the token for currency to token or
+    /// token to currency, the pair of the tokens and the exchanges of both tokens for token to token.
+    /// The routes which can't be resolved are left out.
+    fn get_quote_routes(asset_in: SwapAssetOf<T>, asset_out: SwapAssetOf<T>) -> Vec<Vec<SwapHop<T>>> {
+        let paths = match (asset_in, asset_out) {
+            (SwapAsset::Currency, SwapAsset::Token(token_id)) | (SwapAsset::Token(token_id), SwapAsset::Currency) => {
+                vec![vec![SwapHandler::AssetId(token_id)]]
             }
+            (SwapAsset::Token(token_id), SwapAsset::Token(other_token_id)) if token_id != other_token_id => {
+                let mut paths = Vec::new();
+                if let Some(pair_id) = Self::get_pair_id(token_id, other_token_id) {
+                    paths.push(vec![SwapHandler::ExchangeId(pair_id)]);
+                }
+                paths.push(vec![SwapHandler::AssetId(token_id), SwapHandler::AssetId(other_token_id)]);
+                paths
+            }
+            _ => return Vec::new(),
+        };
+
+        paths
+            .iter()
+            .filter_map(|path| Self::get_swap_hops(asset_in, path).ok())
+            .collect()
+    }
+
+    /// The liquidity position of the owner in the exchange, the amounts are computed as
+    /// `remove_liquidity` does.
+    pub fn get_liquidity_position(
+        exchange_id: T::ExchangeId,
+        owner: T::AccountId,
+    ) -> Option<LiquidityPosition<T::AssetId, TokenBalance<T>, BalanceOf<T>, T::ExchangeId>> {
+        let exchange = Self::get_exchange(exchange_id)?;
+        Self::get_exchange_liquidity_position(exchange_id, &exchange, &owner).ok()
+    }
+
+    /// The liquidity positions of the owner in all exchanges ordered by id, the exchanges
+    /// without the liquidity of the owner are skipped.
+    pub fn get_liquidity_positions(
+        owner: T::AccountId,
+    ) -> Vec<LiquidityPosition<T::AssetId, TokenBalance<T>, BalanceOf<T>, T::ExchangeId>> {
+        Self::get_exchanges()
+            .into_iter()
+            .filter_map(|exchange_info| {
+                Self::get_exchange_liquidity_position(exchange_info.exchange_id, &exchange_info.exchange, &owner).ok()
+            })
+            .filter(|position| !position.liquidity.is_zero())
+            .collect::<Vec<_>>()
+    }
+
+    fn get_exchange_liquidity_position(
+        exchange_id: T::ExchangeId,
+        exchange: &ExchangeOf<T>,
+        owner: &T::AccountId,
+    ) -> Result<LiquidityPosition<T::AssetId, TokenBalance<T>, BalanceOf<T>, T::ExchangeId>, Error<T>> {
+        let liquidity = <zenlink_assets::Module<T>>::balance_of(&exchange.liquidity_id, owner);
+        let protocol_fee = Self::calculate_protocol_fee(exchange_id, exchange.liquidity_id, Self::get_k(exchange)?)?;
+        let total_liquidity = Self::total_liquidity_with_fee(exchange.liquidity_id, &protocol_fee)?;
+
+        let mut position = LiquidityPosition {
+            exchange_id,
+            liquidity_id: exchange.liquidity_id,
+            liquidity,
+            total_liquidity,
+            share: Perbill::zero(),
+            currency_amount: Zero::zero(),
+            token_amount: Zero::zero(),
+        };
+        if total_liquidity.is_zero() {
+            return Ok(position);
         }
 
-        exchanges
+        let liquidity = Self::to_u256(liquidity)?;
+        let total_liquidity = Self::to_u256(total_liquidity)?;
+        let share = Self::mul_div(liquidity, U256::from(Perbill::one().deconstruct()), total_liquidity)?;
+        position.share = Perbill::from_parts(share.low_u32());
+        position.currency_amount = Self::from_u256(Self::mul_div(liquidity, Self::to_u256(Self::get_currency_reserve(exchange))?, total_liquidity)?)?;
+        position.token_amount = Self::from_u256(Self::mul_div(liquidity, Self::to_u256(Self::get_token_reserve(exchange))?, total_liquidity)?)?;
+
+        Ok(position)
+    }
+
+    /// Find the route through the exchanges and pairs with the most output for the exact input
+    /// amount, or with the least input for the exact output amount. The routes have at most
+    /// `max_hops` hops, capped by `MAX_ROUTE_HOPS`, and are priced as the path swaps do.
+    ///
+    /// The best amount of every asset is relaxed hop by hop over the pools read once, from
+    /// `asset_in` for the exact input or back from `asset_out` for the exact output, so the
+    /// search is linear in the hops instead of enumerating every route.
+    pub fn find_best_route(
+        asset_in: SwapAssetOf<T>,
+        asset_out: SwapAssetOf<T>,
+        amount: AssetBalance,
+        max_hops: u32,
+        kind: SwapKind,
+    ) -> Option<SwapRoute<T::ExchangeId, T::AssetId, AssetBalance>> {
+        if asset_in == asset_out || amount.is_zero() {
+            return None;
+        }
+        let amount = Self::to_u256(amount).ok()?;
+
+        // Both directions of every pool open to swaps.
+        let hops = <Exchanges<T>>::iter()
+            .flat_map(|(exchange_id, exchange)| {
+                vec![(exchange_id, SwapAsset::Currency), (exchange_id, SwapAsset::Token(exchange.token_id))]
+            })
+            .chain(<Pairs<T>>::iter()
+                .flat_map(|(pair_id, pair)| vec![(pair_id, SwapAsset::Token(pair.token_0)), (pair_id, SwapAsset::Token(pair.token_1))]))
+            .filter(|(exchange_id, _)| Self::ensure_swap_allowed(*exchange_id).is_ok())
+            .filter_map(|(exchange_id, asset)| Self::get_swap_hop(exchange_id, asset).ok())
+            .collect::<Vec<_>>();
+
+        let (start, target) = match kind {
+            SwapKind::ExactIn => (asset_in, asset_out),
+            SwapKind::ExactOut => (asset_out, asset_in),
+        };
+        let is_better = |amount: U256, other: U256| match kind {
+            SwapKind::ExactIn => amount > other,
+            SwapKind::ExactOut => amount < other,
+        };
+
+        // The best amount of every asset reached in the hops so far, with the pools of its
+        // route from `start`.
+        let mut reached: Vec<(SwapAssetOf<T>, U256, Vec<T::ExchangeId>)> = vec![(start, amount, Vec::new())];
+        let mut best: Option<(U256, Vec<T::ExchangeId>)> = None;
+        for _ in 0..max_hops.min(MAX_ROUTE_HOPS) {
+            let mut next_reached: Vec<(SwapAssetOf<T>, U256, Vec<T::ExchangeId>)> = Vec::new();
+            for (asset, asset_amount, route) in reached.iter() {
+                for hop in hops.iter() {
+                    let (from, to, next_amount) = match kind {
+                        SwapKind::ExactIn => (hop.asset_in, hop.asset_out,
+                            Self::get_input_price(*asset_amount, hop.reserve_in, hop.reserve_out, hop.fee_rate)),
+                        SwapKind::ExactOut => (hop.asset_out, hop.asset_in,
+                            Self::get_output_price(*asset_amount, hop.reserve_in, hop.reserve_out, hop.fee_rate)),
+                    };
+                    // The reserves of a repeated pool would be outdated.
+                    if from != *asset || to == start || route.contains(&hop.exchange_id) {
+                        continue;
+                    }
+                    let next_amount = match next_amount {
+                        Ok(next_amount) if !next_amount.is_zero() => next_amount,
+                        _ => continue,
+                    };
+
+                    match next_reached.iter_mut().find(|(reached_asset, _, _)| *reached_asset == to) {
+                        Some(entry) => if is_better(next_amount, entry.1) {
+                            entry.1 = next_amount;
+                            entry.2 = route.clone();
+                            entry.2.push(hop.exchange_id);
+                        },
+                        None => {
+                            let mut next_route = route.clone();
+                            next_route.push(hop.exchange_id);
+                            next_reached.push((to, next_amount, next_route));
+                        }
+                    }
+                }
+            }
+
+            // The shorter route wins the tie, the routes don't go on from the target.
+            if let Some(index) = next_reached.iter().position(|(reached_asset, _, _)| *reached_asset == target) {
+                let (_, target_amount, route) = next_reached.swap_remove(index);
+                if best.as_ref().map_or(true, |(best_amount, _)| is_better(target_amount, *best_amount)) {
+                    best = Some((target_amount, route));
+                }
+            }
+            reached = next_reached;
+        }
+
+        let (_, mut route) = best?;
+        if kind == SwapKind::ExactOut {
+            route.reverse();
+        }
+        let path = route.into_iter().map(SwapHandler::ExchangeId).collect::<Vec<_>>();
+        let hops = Self::get_swap_hops(asset_in, &path).ok()?;
+        let amounts = match kind {
+            SwapKind::ExactIn => Self::get_amounts_out(&hops, amount),
+            SwapKind::ExactOut => Self::get_amounts_in(&hops, amount),
+        }.ok()?;
+        let amounts = amounts
+            .into_iter()
+            .map(Self::from_u256)
+            .collect::<Result<Vec<AssetBalance>, _>>()
+            .ok()?;
+
+        Some(SwapRoute { path, amounts })
+    }
+
+    /// The price impact as `1 - lesser / greater`, of the amounts at the trade price and at the current price.
+    fn get_price_impact(lesser: U256, greater: U256) -> Permill {
+        if greater.is_zero() || lesser >= greater {
+            return Permill::zero();
+        }
+
+        let parts = (greater - lesser) * U256::from(Permill::one().deconstruct()) / greater;
+        Permill::from_parts(parts.low_u32())
     }
 }
 
@@ -157,6 +560,25 @@ mod rpc_tests {
         });
     }
 
+    #[test]
+    fn exchange_info_v1_should_decode_and_convert() {
+        // The encoding of the exchange info of version 1 of the runtime api
+        let encoded = (0u32, 1u32, EXCHANGE_ACCOUNT, 2000u64, 1000u128, 0u32).encode();
+        let exchange_info = ExchangeInfoV1::<u128, u32, u64, u128, u32>::decode(&mut &encoded[..]).unwrap();
+
+        assert_eq!(ExchangeInfo::from(exchange_info), ExchangeInfo {
+            exchange: Exchange {
+                token_id: 0,
+                liquidity_id: 1,
+                account: EXCHANGE_ACCOUNT,
+                fee_rate: None,
+                token_reserve: 2000,
+                currency_reserve: 1000,
+            },
+            exchange_id: 0,
+        });
+    }
+
     #[test]
     fn rpc_get_exchanges_should_work() {
         new_test_ext().execute_with(|| {
@@ -170,21 +592,14 @@ mod rpc_tests {
                                token_id: 0,
                                liquidity_id: 1,
                                account: 15310315390164549602772283245,
+                               fee_rate: None,
+                               token_reserve: 0,
+                               currency_reserve: 0,
                            },
-                           token_reserve: 0,
-                           currency_reserve: 0,
                            exchange_id: 0,
                        }
             );
 
-            // Alice approve 1000 token for EXCHANGE_ACCOUNT
-            assert_ok!(TokenModule::inner_approve(
-                &0,
-                &ALICE,
-                &EXCHANGE_ACCOUNT,
-                1000
-            ));
-
             // Add 1000 currency and 100 token
             assert_ok!(DexModule::add_liquidity(
                 Origin::signed(ALICE),
@@ -201,12 +616,327 @@ mod rpc_tests {
                                token_id: 0,
                                liquidity_id: 1,
                                account: 15310315390164549602772283245,
+                               fee_rate: None,
+                               token_reserve: 1000,
+                               currency_reserve: 100,
                            },
-                           token_reserve: 1000,
-                           currency_reserve: 100,
                            exchange_id: 0,
                        }
             );
         });
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn rpc_get_exchanges_paged_should_work() {
+        new_test_ext().execute_with(|| {
+            for exchange_id in 0..3 {
+                assert_eq!(TokenModule::inner_issue(&ALICE, 10000, TEST_TOKEN), exchange_id * 2);
+                assert_ok!(DexModule::create_exchange(Origin::signed(ALICE), exchange_id * 2));
+            }
+
+            // Add 1000 currency and 100 token to the exchange 1
+            assert_ok!(DexModule::add_liquidity(
+                Origin::signed(ALICE),
+                SwapHandler::from_exchange_id(1),
+                100,
+                0,
+                1000,
+                100
+            ));
+
+            let ids = |page: &ExchangePage<_, _, _, _, u32>| page
+                .exchanges
+                .iter()
+                .map(|exchange_info| exchange_info.exchange_id)
+                .collect::<Vec<_>>();
+
+            let page = DexModule::get_exchanges_paged(None, 2, Default::default());
+            assert_eq!(ids(&page), vec![0, 1]);
+            assert_eq!(page.next_cursor, Some(2));
+            assert_eq!(page.total, 3);
+
+            let page = DexModule::get_exchanges_paged(page.next_cursor, 2, Default::default());
+            assert_eq!(ids(&page), vec![2]);
+            assert_eq!(page.next_cursor, None);
+            assert_eq!(page.total, 3);
+
+            let by_token = ExchangeFilter { token_id: Some(4), min_currency_reserve: None };
+            let page = DexModule::get_exchanges_paged(None, 2, by_token);
+            assert_eq!(ids(&page), vec![2]);
+            assert_eq!(page.total, 1);
+
+            let by_reserve = ExchangeFilter { token_id: None, min_currency_reserve: Some(100) };
+            let page = DexModule::get_exchanges_paged(None, 2, by_reserve);
+            assert_eq!(ids(&page), vec![1]);
+            assert_eq!(page.exchanges[0].exchange.token_reserve, 1000);
+            assert_eq!(page.total, 1);
+
+            // The limit is capped
+            let page = DexModule::get_exchanges_paged(None, u32::max_value(), Default::default());
+            assert_eq!(ids(&page), vec![0, 1, 2]);
+            assert_eq!(page.next_cursor, None);
+        });
+    }
+
+    #[test]
+    fn rpc_get_liquidity_positions_should_work() {
+        new_test_ext().execute_with(|| {
+            const BOB: u128 = 2;
+
+            assert_eq!(TokenModule::inner_issue(&ALICE, 10000, TEST_TOKEN), 0);
+            assert_ok!(DexModule::create_exchange(Origin::signed(ALICE), 0));
+            assert_eq!(TokenModule::inner_issue(&ALICE, 10000, TEST_TOKEN), 2);
+            assert_ok!(DexModule::create_exchange(Origin::signed(ALICE), 2));
+
+            // Add 1000 token and 100 currency, then give a quarter of the liquidity to Bob
+            assert_ok!(DexModule::add_liquidity(
+                Origin::signed(ALICE),
+                SwapHandler::from_exchange_id(0),
+                100,
+                0,
+                1000,
+                100
+            ));
+            assert_ok!(TokenModule::inner_transfer(&1, &ALICE, &BOB, 25));
+
+            assert_eq!(DexModule::get_liquidity_position(0, ALICE),
+                       Some(LiquidityPosition {
+                           exchange_id: 0,
+                           liquidity_id: 1,
+                           liquidity: 75,
+                           total_liquidity: 100,
+                           share: Perbill::from_percent(75),
+                           currency_amount: 75,
+                           token_amount: 750,
+                       })
+            );
+            assert_eq!(DexModule::get_liquidity_position(0, BOB).map(|position| position.share),
+                       Some(Perbill::from_percent(25)));
+
+            // No liquidity in the exchange
+            assert_eq!(DexModule::get_liquidity_position(1, ALICE),
+                       Some(LiquidityPosition {
+                           exchange_id: 1,
+                           liquidity_id: 3,
+                           ..Default::default()
+                       })
+            );
+            assert_eq!(DexModule::get_liquidity_position(2, ALICE), None);
+
+            let positions = DexModule::get_liquidity_positions(ALICE);
+            assert_eq!(positions.len(), 1);
+            assert_eq!(positions[0].exchange_id, 0);
+            assert!(DexModule::get_liquidity_positions(3).is_empty());
+        });
+    }
+
+    #[test]
+    fn rpc_find_best_route_should_work() {
+        new_test_ext().execute_with(|| {
+            assert_eq!(TokenModule::inner_issue(&ALICE, 10000, TEST_TOKEN), 0);
+            assert_ok!(DexModule::create_exchange(Origin::signed(ALICE), 0));
+            assert_eq!(TokenModule::inner_issue(&ALICE, 10000, TEST_TOKEN), 2);
+            assert_ok!(DexModule::create_exchange(Origin::signed(ALICE), 2));
+            assert_ok!(DexModule::create_pair(Origin::signed(ALICE), 0, 2));
+
+            // Add 1000 token and 100 currency to both exchanges, and 100 of both tokens to the pair
+            for exchange_id in 0..2 {
+                assert_ok!(DexModule::add_liquidity(
+                    Origin::signed(ALICE),
+                    SwapHandler::from_exchange_id(exchange_id),
+                    100,
+                    0,
+                    1000,
+                    100
+                ));
+            }
+            assert_ok!(DexModule::add_pair_liquidity(Origin::signed(ALICE), 0, 2, 100, 0, 100, 100));
+
+            let exchanges_path = vec![SwapHandler::ExchangeId(0), SwapHandler::ExchangeId(1)];
+
+            // The deeper exchanges beat the shallow pair
+            let route = DexModule::find_best_route(SwapAsset::Token(0), SwapAsset::Token(2), 100, 3, SwapKind::ExactIn).unwrap();
+            assert_eq!(route.path, exchanges_path);
+            assert_eq!(route.amounts.len(), 3);
+            assert_eq!(route.amounts[0], 100);
+            assert_eq!(
+                Some(route.amounts[2]),
+                DexModule::get_amount_out(SwapAsset::Token(0), SwapAsset::Token(2), 100).map(|quote| quote.amount)
+            );
+
+            let route = DexModule::find_best_route(SwapAsset::Token(0), SwapAsset::Token(2), 40, 3, SwapKind::ExactOut).unwrap();
+            assert_eq!(route.path, exchanges_path);
+            assert_eq!(route.amounts[2], 40);
+            assert_eq!(
+                Some(route.amounts[0]),
+                DexModule::get_amount_in(SwapAsset::Token(0), SwapAsset::Token(2), 40).map(|quote| quote.amount)
+            );
+
+            // Only the pair is within one hop
+            let route = DexModule::find_best_route(SwapAsset::Token(0), SwapAsset::Token(2), 100, 1, SwapKind::ExactIn).unwrap();
+            assert_eq!(route.path, vec![SwapHandler::ExchangeId(2)]);
+
+            let route = DexModule::find_best_route(SwapAsset::Currency, SwapAsset::Token(2), 10, 3, SwapKind::ExactIn).unwrap();
+            assert_eq!(route.path, vec![SwapHandler::ExchangeId(1)]);
+
+            assert_eq!(DexModule::find_best_route(SwapAsset::Token(0), SwapAsset::Token(0), 100, 3, SwapKind::ExactIn), None);
+            assert_eq!(DexModule::find_best_route(SwapAsset::Token(0), SwapAsset::Token(2), 0, 3, SwapKind::ExactIn), None);
+            // More than the reserves
+            assert_eq!(DexModule::find_best_route(SwapAsset::Token(0), SwapAsset::Token(2), 1000, 3, SwapKind::ExactOut), None);
+        });
+    }
+
+    #[test]
+    fn rpc_find_best_route_among_many_routes_should_work() {
+        new_test_ext().execute_with(|| {
+            assert_eq!(TokenModule::inner_issue(&ALICE, 100000, TEST_TOKEN), 0);
+            assert_ok!(DexModule::create_exchange(Origin::signed(ALICE), 0));
+            assert_eq!(TokenModule::inner_issue(&ALICE, 100000, TEST_TOKEN), 2);
+            assert_ok!(DexModule::create_exchange(Origin::signed(ALICE), 2));
+            assert_eq!(TokenModule::inner_issue(&ALICE, 100000, TEST_TOKEN), 4);
+
+            // The routes from token 0 to token 2: the pair 2, the exchanges 0 and 1 through the
+            // currency, the pairs 3 and 4 through token 4, and the longer ones mixing them
+            assert_ok!(DexModule::create_pair(Origin::signed(ALICE), 0, 2));
+            assert_ok!(DexModule::create_pair(Origin::signed(ALICE), 0, 4));
+            assert_ok!(DexModule::create_pair(Origin::signed(ALICE), 4, 2));
+            for exchange_id in 0..2 {
+                assert_ok!(DexModule::add_liquidity(
+                    Origin::signed(ALICE),
+                    SwapHandler::from_exchange_id(exchange_id),
+                    100,
+                    0,
+                    1000,
+                    100
+                ));
+            }
+            assert_ok!(DexModule::add_pair_liquidity(Origin::signed(ALICE), 0, 2, 100, 0, 100, 100));
+            assert_ok!(DexModule::add_pair_liquidity(Origin::signed(ALICE), 0, 4, 10000, 0, 10000, 100));
+            assert_ok!(DexModule::add_pair_liquidity(Origin::signed(ALICE), 4, 2, 10000, 0, 10000, 100));
+
+            let routes: Vec<Vec<u32>> = vec![vec![2], vec![0, 1], vec![3, 4], vec![3, 4, 1, 0, 2], vec![0, 1, 2, 3, 4]];
+            let amounts_of = |route: &Vec<u32>, kind: SwapKind, amount: u128| {
+                let path = route.iter().map(|id| SwapHandler::ExchangeId(*id)).collect::<Vec<_>>();
+                let hops = DexModule::get_swap_hops(SwapAsset::Token(0), &path).ok()?;
+                match kind {
+                    SwapKind::ExactIn => DexModule::get_amounts_out(&hops, U256::from(amount)),
+                    SwapKind::ExactOut => DexModule::get_amounts_in(&hops, U256::from(amount)),
+                }.ok()
+            };
+
+            // The deep pairs through token 4 beat the other routes
+            let route = DexModule::find_best_route(SwapAsset::Token(0), SwapAsset::Token(2), 100, 3, SwapKind::ExactIn).unwrap();
+            assert_eq!(route.path, vec![SwapHandler::ExchangeId(3), SwapHandler::ExchangeId(4)]);
+            for other_route in routes.iter() {
+                if let Some(amounts) = amounts_of(other_route, SwapKind::ExactIn, 100) {
+                    assert!(U256::from(route.amounts[route.amounts.len() - 1]) >= amounts[amounts.len() - 1]);
+                }
+            }
+
+            let route = DexModule::find_best_route(SwapAsset::Token(0), SwapAsset::Token(2), 50, 3, SwapKind::ExactOut).unwrap();
+            assert_eq!(route.path, vec![SwapHandler::ExchangeId(3), SwapHandler::ExchangeId(4)]);
+            for other_route in routes.iter() {
+                if let Some(amounts) = amounts_of(other_route, SwapKind::ExactOut, 50) {
+                    assert!(U256::from(route.amounts[0]) <= amounts[0]);
+                }
+            }
+
+            // The paused pools are left out of the routes
+            assert_ok!(DexModule::set_exchange_status(
+                Origin::root(),
+                SwapHandler::from_exchange_id(3),
+                ExchangeStatus::SwapsPaused
+            ));
+            let route = DexModule::find_best_route(SwapAsset::Token(0), SwapAsset::Token(2), 100, 3, SwapKind::ExactIn).unwrap();
+            assert_eq!(route.path, vec![SwapHandler::ExchangeId(0), SwapHandler::ExchangeId(1)]);
+        });
+    }
+
+    #[test]
+    fn rpc_get_amount_quotes_should_work() {
+        new_test_ext().execute_with(|| {
+            assert_eq!(TokenModule::inner_issue(&ALICE, 10000, TEST_TOKEN), 0);
+            assert_eq!(TokenModule::inner_issue(&ALICE, 10000, TEST_TOKEN), 1);
+            assert_ok!(DexModule::create_exchange(Origin::signed(ALICE), 0));
+            assert_ok!(DexModule::create_exchange(Origin::signed(ALICE), 1));
+
+            // Add 4000 currency and 4000 token to both exchanges
+            for exchange_id in 0..2 {
+                assert_ok!(DexModule::add_liquidity(
+                    Origin::signed(ALICE),
+                    SwapHandler::from_exchange_id(exchange_id),
+                    4000,
+                    0,
+                    4000,
+                    100
+                ));
+            }
+
+            // 99 token at the current price, the fee excluded
+            assert_eq!(
+                DexModule::get_amount_out(SwapAsset::Currency, SwapAsset::Token(0), 100),
+                Some(AmountQuote { amount: 97, price_impact: Permill::from_parts(20202) })
+            );
+            // Through the currency of both exchanges
+            assert_eq!(
+                DexModule::get_amount_out(SwapAsset::Token(0), SwapAsset::Token(1), 100),
+                Some(AmountQuote { amount: 94, price_impact: Permill::from_parts(40816) })
+            );
+            assert_eq!(
+                DexModule::get_amount_in(SwapAsset::Token(0), SwapAsset::Currency, 100),
+                Some(AmountQuote { amount: 103, price_impact: Permill::from_parts(29126) })
+            );
+            assert_eq!(
+                DexModule::get_amount_in(SwapAsset::Token(0), SwapAsset::Token(1), 100),
+                Some(AmountQuote { amount: 107, price_impact: Permill::from_parts(65420) })
+            );
+
+            assert_eq!(DexModule::get_amount_out(SwapAsset::Token(0), SwapAsset::Token(0), 100), None);
+            assert_eq!(DexModule::get_amount_out(SwapAsset::Currency, SwapAsset::Token(5), 100), None);
+            // More than the reserve
+            assert_eq!(DexModule::get_amount_in(SwapAsset::Currency, SwapAsset::Token(0), 4000), None);
+        });
+    }
+
+    #[test]
+    fn rpc_get_amount_quotes_with_pair_should_work() {
+        new_test_ext().execute_with(|| {
+            assert_eq!(TokenModule::inner_issue(&ALICE, 10000, TEST_TOKEN), 0);
+            assert_eq!(TokenModule::inner_issue(&ALICE, 10000, TEST_TOKEN), 1);
+            assert_ok!(DexModule::create_exchange(Origin::signed(ALICE), 0));
+            assert_ok!(DexModule::create_exchange(Origin::signed(ALICE), 1));
+            for exchange_id in 0..2 {
+                assert_ok!(DexModule::add_liquidity(
+                    Origin::signed(ALICE),
+                    SwapHandler::from_exchange_id(exchange_id),
+                    4000,
+                    0,
+                    4000,
+                    100
+                ));
+            }
+
+            // The pair without liquidity buys nothing, the exchanges are taken
+            assert_ok!(DexModule::create_pair(Origin::signed(ALICE), 0, 1));
+            assert_eq!(
+                DexModule::get_amount_out(SwapAsset::Token(0), SwapAsset::Token(1), 100),
+                Some(AmountQuote { amount: 94, price_impact: Permill::from_parts(40816) })
+            );
+
+            // Add 4000 token and 4000 other token, the pair has one fee less than the exchanges
+            assert_ok!(DexModule::add_pair_liquidity(Origin::signed(ALICE), 0, 1, 4000, 0, 4000, 100));
+            assert_eq!(
+                DexModule::get_amount_out(SwapAsset::Token(0), SwapAsset::Token(1), 100),
+                Some(AmountQuote { amount: 97, price_impact: Permill::from_parts(20202) })
+            );
+            assert_eq!(
+                DexModule::get_amount_in(SwapAsset::Token(0), SwapAsset::Token(1), 100),
+                Some(AmountQuote { amount: 103, price_impact: Permill::from_parts(29126) })
+            );
+            assert_eq!(
+                DexModule::get_amount_in(SwapAsset::Token(1), SwapAsset::Token(0), 100),
+                Some(AmountQuote { amount: 103, price_impact: Permill::from_parts(29126) })
+            );
+        });
+    }
+}
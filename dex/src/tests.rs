@@ -1,7 +1,13 @@
-use crate::{mock::*, Error, SwapHandler};
+use crate::{migration::OldExchange, mock::*, Error, Exchange, ExchangeStatus, Releases, SwapAsset, SwapHandler, MAX_PATH};
+use codec::Encode;
 use frame_support::{
     assert_noop, assert_ok,
+    storage::migration::{get_storage_value, put_storage_value},
+    traits::{Currency as _, OnRuntimeUpgrade},
+    Blake2_128Concat, Blake2_256, StorageHasher, StorageValue,
 };
+use sp_core::U256;
+use sp_runtime::{DispatchError, FixedPointNumber, FixedU128, Permill};
 
 const TEST_TOKEN: &AssetInfo = &AssetInfo {
     name: *b"zenlinktesttoken",
@@ -104,13 +110,6 @@ fn create_more_exchanges_should_work() {
             1
         );
 
-        assert_ok!(TokenModule::inner_approve(
-            &0,
-            &ALICE,
-            &EXCHANGE_ACCOUNT,
-            42
-        ));
-
         assert_eq!(TokenModule::total_supply(&1), 0);
         // Add 420 currency and 42 token
         assert_ok!(DexModule::add_liquidity(
@@ -123,7 +122,6 @@ fn create_more_exchanges_should_work() {
         ));
         assert_eq!(TokenModule::total_supply(&1), 420);
 
-
         // create TEST_OTHER_TOKEN exchange
         assert_eq!(TokenModule::inner_issue(&BOB, 42, TEST_OTHER_TOKEN), 2);
         assert_ok!(DexModule::create_exchange(Origin::signed(BOB), 2));
@@ -141,13 +139,6 @@ fn create_more_exchanges_should_work() {
             3
         );
 
-        assert_ok!(TokenModule::inner_approve(
-            &2,
-            &BOB,
-            &EXCHANGE_ACCOUNT2,
-            42
-        ));
-
         assert_eq!(TokenModule::total_supply(&3), 0);
         // Add 420 currency and 42 other token
         assert_ok!(DexModule::add_liquidity(
@@ -179,14 +170,6 @@ fn add_liquidity_should_work() {
             EXCHANGE_ACCOUNT
         );
 
-        // Alice approve 1000 token for EXCHANGE_ACCOUNT
-        assert_ok!(TokenModule::inner_approve(
-            &0,
-            &ALICE,
-            &EXCHANGE_ACCOUNT,
-            1000
-        ));
-
         // Exchange 0 liquidity is 0
         assert_eq!(TokenModule::total_supply(&1), 0);
 
@@ -264,23 +247,15 @@ fn add_liquidity_should_work() {
             100
         ), Error::<Test>::RequestedZeroLiquidity);
 
-        // (6) AllowanceLow
+        // (6) BalanceLow
         assert_noop!(DexModule::add_liquidity(
-            Origin::signed(ALICE),
+            Origin::signed(BOB),    // Bob has no token
             SwapHandler::from_exchange_id(0),
             100,
             1,
             1000,
             100
-        ), Error::<Test>::AllowanceLow);
-
-        // Alice approve 1000 token for EXCHANGE_ACCOUNT
-        assert_ok!(TokenModule::inner_approve(
-            &0,
-            &ALICE,
-            &EXCHANGE_ACCOUNT,
-            1000
-        ));
+        ), zenlink_assets::Error::<Test>::BalanceLow);
 
         // (7) TooLowLiquidity
         assert_noop!(DexModule::add_liquidity(
@@ -332,12 +307,6 @@ fn remove_liquidity_should_work() {
     new_test_ext().execute_with(|| {
         assert_eq!(TokenModule::inner_issue(&ALICE, 5000, TEST_TOKEN), 0);
         assert_ok!(DexModule::create_exchange(Origin::signed(ALICE), 0));
-        assert_ok!(TokenModule::inner_approve(
-            &0,
-            &ALICE,
-            &EXCHANGE_ACCOUNT,
-            1000
-        ));
 
         // Add 100 currency and 500 token
         assert_ok!(DexModule::add_liquidity(
@@ -436,12 +405,6 @@ fn currency_to_token_input_should_work() {
     new_test_ext().execute_with(|| {
         assert_eq!(TokenModule::inner_issue(&ALICE, 42, TEST_TOKEN), 0);
         assert_ok!(DexModule::create_exchange(Origin::signed(ALICE), 0));
-        assert_ok!(TokenModule::inner_approve(
-            &0,
-            &ALICE,
-            &EXCHANGE_ACCOUNT,
-            42
-        ));
 
         // Add 420 currency and 42 token
         assert_ok!(DexModule::add_liquidity(
@@ -495,12 +458,6 @@ fn currency_to_token_output_should_work() {
     new_test_ext().execute_with(|| {
         assert_eq!(TokenModule::inner_issue(&ALICE, 42, TEST_TOKEN), 0);
         assert_ok!(DexModule::create_exchange(Origin::signed(ALICE), 0));
-        assert_ok!(TokenModule::inner_approve(
-            &0,
-            &ALICE,
-            &EXCHANGE_ACCOUNT,
-            42
-        ));
 
         // Add 420 currency and 42 token
         assert_ok!(DexModule::add_liquidity(
@@ -550,12 +507,6 @@ fn token_to_currency_input_should_work() {
         assert_eq!(TokenModule::inner_issue(&ALICE, 42*2, TEST_TOKEN), 0);
         assert_ok!(TokenModule::inner_transfer(&0, &ALICE, &BOB, 42));
         assert_ok!(DexModule::create_exchange(Origin::signed(ALICE), 0));
-        assert_ok!(TokenModule::inner_approve(
-            &0,
-            &ALICE,
-            &EXCHANGE_ACCOUNT,
-            42
-        ));
 
         // Add 420 currency and 42 token
         assert_ok!(DexModule::add_liquidity(
@@ -570,13 +521,6 @@ fn token_to_currency_input_should_work() {
         // Total supply liquidity is 420
         assert_eq!(TokenModule::total_supply(&1), 420);
 
-        assert_ok!(TokenModule::inner_approve(
-            &0,
-            &BOB,
-            &EXCHANGE_ACCOUNT,
-            42
-        ));
-
         assert_noop!(DexModule::token_to_currency_input(
             Origin::signed(BOB),
             SwapHandler::from_exchange_id(0),
@@ -612,12 +556,6 @@ fn token_to_currency_output_should_work() {
         assert_eq!(TokenModule::inner_issue(&ALICE, 42*2, TEST_TOKEN), 0);
         assert_ok!(TokenModule::inner_transfer(&0, &ALICE, &BOB, 42));
         assert_ok!(DexModule::create_exchange(Origin::signed(ALICE), 0));
-        assert_ok!(TokenModule::inner_approve(
-            &0,
-            &ALICE,
-            &EXCHANGE_ACCOUNT,
-            42
-        ));
 
         // Add 420 currency and 42 token
         assert_ok!(DexModule::add_liquidity(
@@ -632,13 +570,6 @@ fn token_to_currency_output_should_work() {
         // Total supply liquidity is 420
         assert_eq!(TokenModule::total_supply(&1), 420);
 
-        assert_ok!(TokenModule::inner_approve(
-            &0,
-            &BOB,
-            &EXCHANGE_ACCOUNT,
-            42
-        ));
-
         assert_noop!(DexModule::token_to_currency_output(
             Origin::signed(BOB),
             SwapHandler::from_asset_id(0),
@@ -674,12 +605,6 @@ fn token_to_token_input_should_work() {
         // create TEST_TOKEN exchange
         assert_eq!(TokenModule::inner_issue(&ALICE, 42+42, TEST_TOKEN), 0);
         assert_ok!(DexModule::create_exchange(Origin::signed(ALICE), 0));
-        assert_ok!(TokenModule::inner_approve(
-            &0,
-            &ALICE,
-            &EXCHANGE_ACCOUNT,
-            42
-        ));
         // Add 420 currency and 42 token
         assert_ok!(DexModule::add_liquidity(
             Origin::signed(ALICE),
@@ -694,12 +619,6 @@ fn token_to_token_input_should_work() {
         // create TEST_OTHER_TOKEN exchange
         assert_eq!(TokenModule::inner_issue(&BOB, 42, TEST_OTHER_TOKEN), 2);
         assert_ok!(DexModule::create_exchange(Origin::signed(BOB), 2));
-        assert_ok!(TokenModule::inner_approve(
-            &2,
-            &BOB,
-            &EXCHANGE_ACCOUNT2,
-            42
-        ));
         // Add 420 currency and 42 other token
         assert_ok!(DexModule::add_liquidity(
             Origin::signed(BOB),
@@ -717,13 +636,6 @@ fn token_to_token_input_should_work() {
         assert_eq!(TokenModule::balance_of(&0, &CHAREL), 42);
         assert_eq!(TokenModule::balance_of(&2, &CHAREL), 0);
 
-        assert_ok!(TokenModule::inner_approve(
-            &0,
-            &CHAREL,
-            &EXCHANGE_ACCOUNT,
-            42
-        ));
-
         assert_noop!(DexModule::token_to_token_input(
             Origin::signed(CHAREL),
             SwapHandler::from_asset_id(0),
@@ -758,12 +670,6 @@ fn token_to_token_output_should_work() {
         // create TEST_TOKEN exchange
         assert_eq!(TokenModule::inner_issue(&ALICE, 42+42, TEST_TOKEN), 0);
         assert_ok!(DexModule::create_exchange(Origin::signed(ALICE), 0));
-        assert_ok!(TokenModule::inner_approve(
-            &0,
-            &ALICE,
-            &EXCHANGE_ACCOUNT,
-            42
-        ));
         // Add 420 currency and 42 token
         assert_ok!(DexModule::add_liquidity(
             Origin::signed(ALICE),
@@ -778,12 +684,6 @@ fn token_to_token_output_should_work() {
         // create TEST_OTHER_TOKEN exchange
         assert_eq!(TokenModule::inner_issue(&BOB, 42, TEST_OTHER_TOKEN), 2);
         assert_ok!(DexModule::create_exchange(Origin::signed(BOB), 2));
-        assert_ok!(TokenModule::inner_approve(
-            &2,
-            &BOB,
-            &EXCHANGE_ACCOUNT2,
-            42
-        ));
         // Add 420 currency and 42 other token
         assert_ok!(DexModule::add_liquidity(
             Origin::signed(BOB),
@@ -801,13 +701,6 @@ fn token_to_token_output_should_work() {
         assert_eq!(TokenModule::balance_of(&0, &CHAREL), 42);
         assert_eq!(TokenModule::balance_of(&2, &CHAREL), 0);
 
-        assert_ok!(TokenModule::inner_approve(
-            &0,
-            &CHAREL,
-            &EXCHANGE_ACCOUNT,
-            42
-        ));
-
         assert_noop!(DexModule::token_to_token_output(
             Origin::signed(CHAREL),
             SwapHandler::from_exchange_id(0),
@@ -841,12 +734,6 @@ fn zlk_liquidity_transfer_and_remove_should_work() {
     new_test_ext().execute_with(|| {
         assert_eq!(TokenModule::inner_issue(&ALICE, 42, TEST_TOKEN), 0);
         assert_ok!(DexModule::create_exchange(Origin::signed(ALICE), 0));
-        assert_ok!(TokenModule::inner_approve(
-            &0,
-            &ALICE,
-            &EXCHANGE_ACCOUNT,
-            42
-        ));
 
         // Add 420 currency and 42 token
         assert_ok!(DexModule::add_liquidity(
@@ -907,4 +794,1798 @@ fn zlk_liquidity_transfer_and_remove_should_work() {
         assert_eq!(TokenModule::total_supply(&1), 0);
         assert_eq!(TokenModule::balance_of(&1, &ALICE), 0);
     })
-}
\ No newline at end of file
+}
+
+#[test]
+fn set_exchange_fee_rate_should_work() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(TokenModule::inner_issue(&ALICE, 1000, TEST_TOKEN), 0);
+        assert_ok!(DexModule::create_exchange(Origin::signed(ALICE), 0));
+
+        // Add 1000 currency and 1000 token
+        assert_ok!(DexModule::add_liquidity(
+            Origin::signed(ALICE),
+            SwapHandler::from_exchange_id(0),
+            1000,
+            0,
+            1000,
+            100
+        ));
+
+        let exchange = DexModule::get_exchange_info(0).unwrap();
+        assert_eq!(DexModule::fee_rate_of(&exchange), Permill::from_parts(3_000));
+        assert_eq!(DexModule::get_currency_to_token_input_price(&exchange, 1000), Ok(499));
+
+        assert_noop!(DexModule::set_exchange_fee_rate(
+            Origin::signed(ALICE),  // not the control origin
+            SwapHandler::from_exchange_id(0),
+            Some(Permill::from_percent(1))
+        ), DispatchError::BadOrigin);
+
+        assert_noop!(DexModule::set_exchange_fee_rate(
+            Origin::root(),
+            SwapHandler::from_exchange_id(0),
+            Some(Permill::one())    // fee rate must be less than 100%
+        ), Error::<Test>::InvalidFeeRate);
+
+        assert_noop!(DexModule::set_exchange_fee_rate(
+            Origin::root(),
+            SwapHandler::from_exchange_id(1),   // no exchange
+            Some(Permill::from_percent(1))
+        ), Error::<Test>::ExchangeNotExists);
+
+        assert_ok!(DexModule::set_exchange_fee_rate(
+            Origin::root(),
+            SwapHandler::from_asset_id(0),
+            Some(Permill::from_percent(1))
+        ));
+
+        let exchange = DexModule::get_exchange_info(0).unwrap();
+        assert_eq!(DexModule::fee_rate_of(&exchange), Permill::from_percent(1));
+        assert_eq!(DexModule::get_currency_to_token_input_price(&exchange, 1000), Ok(497));
+
+        assert_ok!(DexModule::currency_to_token_input(
+            Origin::signed(BOB),
+            SwapHandler::from_exchange_id(0),
+            1000,
+            1,
+            100,
+            BOB
+        ));
+
+        assert_eq!(Currency::free_balance(BOB), 10000 - 1000);
+        assert_eq!(TokenModule::balance_of(&0, &BOB), 497);
+        assert_eq!(TokenModule::balance_of(&0, &EXCHANGE_ACCOUNT), 1000 - 497);
+
+        // Back to the default fee rate
+        assert_ok!(DexModule::set_exchange_fee_rate(
+            Origin::root(),
+            SwapHandler::from_exchange_id(0),
+            None
+        ));
+        assert_eq!(DexModule::get_exchange_info(0).unwrap().fee_rate, None);
+    })
+}
+
+#[test]
+fn protocol_fee_should_work() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(TokenModule::inner_issue(&ALICE, 5000, TEST_TOKEN), 0);
+        assert_ok!(DexModule::create_exchange(Origin::signed(ALICE), 0));
+
+        assert_noop!(
+            DexModule::set_fee_to(Origin::signed(ALICE), Some(CHAREL)),
+            DispatchError::BadOrigin
+        );
+        assert_ok!(DexModule::set_fee_to(Origin::root(), Some(CHAREL)));
+        assert_eq!(DexModule::fee_to(), Some(CHAREL));
+
+        // 10% fee rate makes the growth of k obvious
+        assert_ok!(DexModule::set_exchange_fee_rate(
+            Origin::root(),
+            SwapHandler::from_exchange_id(0),
+            Some(Permill::from_percent(10))
+        ));
+
+        // Add 5000 currency and 5000 token
+        assert_ok!(DexModule::add_liquidity(
+            Origin::signed(ALICE),
+            SwapHandler::from_exchange_id(0),
+            5000,
+            0,
+            5000,
+            100
+        ));
+        assert_eq!(TokenModule::total_supply(&1), 5000);
+        assert_eq!(DexModule::k_last(0), U256::from(5000 * 5000));
+
+        assert_ok!(DexModule::currency_to_token_input(
+            Origin::signed(BOB),
+            SwapHandler::from_exchange_id(0),
+            4000,
+            1,
+            100,
+            BOB
+        ));
+        assert_eq!(TokenModule::balance_of(&0, &BOB), 2093);
+
+        // sqrt(9000 * 2907) = 5114, the protocol fee is 5000 * (5114 - 5000) / (5114 * 5 + 5000) = 18
+        assert_ok!(DexModule::remove_liquidity(
+            Origin::signed(ALICE),
+            SwapHandler::from_exchange_id(0),
+            1000,
+            1,
+            1,
+            100
+        ));
+
+        assert_eq!(TokenModule::balance_of(&1, &CHAREL), 18);
+        assert_eq!(TokenModule::total_supply(&1), 5018 - 1000);
+        // Remove 1000 * 9000 / 5018 currency and 1000 * 2907 / 5018 token
+        assert_eq!(Currency::free_balance(EXCHANGE_ACCOUNT), 9000 - 1793);
+        assert_eq!(TokenModule::balance_of(&0, &EXCHANGE_ACCOUNT), 2907 - 579);
+        assert_eq!(DexModule::k_last(0), U256::from((9000 - 1793) * (2907 - 579)));
+
+        // Turn off the protocol fee
+        assert_ok!(DexModule::set_fee_to(Origin::root(), None));
+        assert_ok!(DexModule::remove_liquidity(
+            Origin::signed(ALICE),
+            SwapHandler::from_exchange_id(0),
+            1000,
+            1,
+            1,
+            100
+        ));
+
+        assert_eq!(TokenModule::balance_of(&1, &CHAREL), 18);
+        assert_eq!(DexModule::k_last(0), U256::zero());
+    })
+}
+
+#[test]
+fn create_pair_should_work() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(TokenModule::inner_issue(&ALICE, 10000, TEST_TOKEN), 0);
+        assert_eq!(TokenModule::inner_issue(&ALICE, 10000, TEST_OTHER_TOKEN), 1);
+
+        // The exchange and the pair share the exchange id
+        assert_ok!(DexModule::create_exchange(Origin::signed(ALICE), 0));
+        assert_ok!(DexModule::create_pair(Origin::signed(ALICE), 1, 0));
+
+        assert_eq!(DexModule::get_pair_id(0, 1), Some(1));
+        assert_eq!(DexModule::get_pair_id(1, 0), Some(1));
+
+        let pair = DexModule::get_pair(1).unwrap();
+        assert_eq!(pair.token_0, 0);
+        assert_eq!(pair.token_1, 1);
+        assert_eq!(pair.liquidity_id, 3);
+        assert_eq!(pair.account, EXCHANGE_ACCOUNT2);
+        assert_eq!(TokenModule::total_supply(&3), 0);
+        assert_eq!(DexModule::zlk_to_exchange(3), Some(1));
+    })
+}
+
+#[test]
+fn create_pair_should_not_work() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(TokenModule::inner_issue(&ALICE, 10000, TEST_TOKEN), 0);
+
+        assert_noop!(
+            DexModule::create_pair(Origin::signed(ALICE), 0, 0),
+            Error::<Test>::IdenticalTokens
+        );
+        assert_noop!(
+            DexModule::create_pair(Origin::signed(ALICE), 0, 1),
+            Error::<Test>::TokenNotExists
+        );
+
+        assert_eq!(TokenModule::inner_issue(&ALICE, 10000, TEST_OTHER_TOKEN), 1);
+        assert_ok!(DexModule::create_pair(Origin::signed(ALICE), 0, 1));
+
+        assert_noop!(
+            DexModule::create_pair(Origin::signed(ALICE), 1, 0),
+            Error::<Test>::PairAlreadyExists
+        );
+        // The liquidity token of the pair
+        assert_noop!(
+            DexModule::create_pair(Origin::signed(ALICE), 0, 2),
+            Error::<Test>::DeniedSwap
+        );
+    })
+}
+
+#[test]
+fn pair_liquidity_and_swap_should_work() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(TokenModule::inner_issue(&ALICE, 10000, TEST_TOKEN), 0);
+        assert_eq!(TokenModule::inner_issue(&ALICE, 10000, TEST_OTHER_TOKEN), 1);
+        assert_ok!(DexModule::create_pair(Origin::signed(ALICE), 0, 1));
+
+        assert_noop!(DexModule::add_pair_liquidity(
+            Origin::signed(ALICE),
+            0,
+            5,  // no pair
+            1000,
+            0,
+            4000,
+            100
+        ), Error::<Test>::PairNotExists);
+
+        // Add 1000 token and 4000 other token, the liquidity is sqrt(1000 * 4000)
+        assert_ok!(DexModule::add_pair_liquidity(
+            Origin::signed(ALICE),
+            0,
+            1,
+            1000,
+            0,
+            4000,
+            100
+        ));
+        assert_eq!(TokenModule::total_supply(&2), 2000);
+        assert_eq!(TokenModule::balance_of(&0, &EXCHANGE_ACCOUNT), 1000);
+        assert_eq!(TokenModule::balance_of(&1, &EXCHANGE_ACCOUNT), 4000);
+
+        assert_noop!(DexModule::add_pair_liquidity(
+            Origin::signed(ALICE),
+            1,
+            0,
+            400,
+            1,
+            99, // max token is set too low
+            100
+        ), Error::<Test>::TooManyToken);
+
+        // Add 400 other token and 100 token in the reversed order
+        assert_ok!(DexModule::add_pair_liquidity(
+            Origin::signed(ALICE),
+            1,
+            0,
+            400,
+            200,
+            200,
+            100
+        ));
+        assert_eq!(TokenModule::total_supply(&2), 2200);
+        assert_eq!(TokenModule::balance_of(&0, &EXCHANGE_ACCOUNT), 1100);
+        assert_eq!(TokenModule::balance_of(&1, &EXCHANGE_ACCOUNT), 4400);
+
+        assert_ok!(TokenModule::inner_transfer(&0, &ALICE, &BOB, 1000));
+
+        assert_noop!(DexModule::token_to_token_pair_input(
+            Origin::signed(BOB),
+            0,
+            1,
+            100,
+            366,    // min other token set too high
+            100,
+            BOB
+        ), Error::<Test>::NotEnoughToken);
+
+        assert_ok!(DexModule::token_to_token_pair_input(
+            Origin::signed(BOB),
+            0,
+            1,
+            100,
+            365,
+            100,
+            BOB
+        ));
+        assert_eq!(TokenModule::balance_of(&0, &BOB), 1000 - 100);
+        assert_eq!(TokenModule::balance_of(&1, &BOB), 365);
+
+        assert_noop!(DexModule::token_to_token_pair_output(
+            Origin::signed(BOB),
+            0,
+            1,
+            35,
+            10,     // max token set too low
+            100,
+            BOB
+        ), Error::<Test>::TooExpensiveToken);
+
+        assert_ok!(DexModule::token_to_token_pair_output(
+            Origin::signed(BOB),
+            0,
+            1,
+            35,
+            11,
+            100,
+            BOB
+        ));
+        assert_eq!(TokenModule::balance_of(&0, &BOB), 1000 - 100 - 11);
+        assert_eq!(TokenModule::balance_of(&1, &BOB), 365 + 35);
+        assert_eq!(TokenModule::balance_of(&0, &EXCHANGE_ACCOUNT), 1211);
+        assert_eq!(TokenModule::balance_of(&1, &EXCHANGE_ACCOUNT), 4000);
+
+        assert_noop!(DexModule::remove_pair_liquidity(
+            Origin::signed(ALICE),
+            0,
+            1,
+            220,
+            122,    // min token set too high
+            1,
+            100
+        ), Error::<Test>::NotEnoughToken);
+
+        // Remove 220 * 1211 / 2200 token and 220 * 4000 / 2200 other token
+        assert_ok!(DexModule::remove_pair_liquidity(
+            Origin::signed(ALICE),
+            0,
+            1,
+            220,
+            121,
+            400,
+            100
+        ));
+        assert_eq!(TokenModule::total_supply(&2), 2200 - 220);
+        assert_eq!(TokenModule::balance_of(&2, &ALICE), 2200 - 220);
+        assert_eq!(TokenModule::balance_of(&0, &ALICE), 10000 - 1000 - 1000 - 100 + 121);
+        assert_eq!(TokenModule::balance_of(&1, &ALICE), 10000 - 4000 - 400 + 400);
+    })
+}
+
+#[test]
+fn swap_by_path_should_work() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(TokenModule::inner_issue(&ALICE, 10000, TEST_TOKEN), 0);
+        assert_eq!(TokenModule::inner_issue(&ALICE, 10000, TEST_OTHER_TOKEN), 1);
+
+        // The exchange 0 of currency and token 0, the pair 1 of token 0 and token 1
+        assert_ok!(DexModule::create_exchange(Origin::signed(ALICE), 0));
+        assert_ok!(DexModule::create_pair(Origin::signed(ALICE), 0, 1));
+        assert_ok!(DexModule::add_liquidity(
+            Origin::signed(ALICE),
+            SwapHandler::from_exchange_id(0),
+            1000,
+            0,
+            1000,
+            100
+        ));
+        assert_ok!(DexModule::add_pair_liquidity(
+            Origin::signed(ALICE),
+            0,
+            1,
+            1000,
+            0,
+            1000,
+            100
+        ));
+
+        let path = vec![SwapHandler::from_exchange_id(0), SwapHandler::from_exchange_id(1)];
+
+        assert_noop!(DexModule::swap_exact_in_by_path(
+            Origin::signed(BOB),
+            SwapAsset::Currency,
+            vec![],
+            100,
+            1,
+            100,
+            BOB
+        ), Error::<Test>::InvalidPath);
+
+        assert_noop!(DexModule::swap_exact_in_by_path(
+            Origin::signed(BOB),
+            SwapAsset::Currency,
+            vec![SwapHandler::from_exchange_id(0); MAX_PATH as usize + 1],
+            100,
+            1,
+            100,
+            BOB
+        ), Error::<Test>::PathTooLong);
+
+        assert_noop!(DexModule::swap_exact_in_by_path(
+            Origin::signed(BOB),
+            SwapAsset::Currency,
+            vec![SwapHandler::from_exchange_id(1)],  // the pair can't sell currency
+            100,
+            1,
+            100,
+            BOB
+        ), Error::<Test>::InvalidPath);
+
+        assert_noop!(DexModule::swap_exact_in_by_path(
+            Origin::signed(BOB),
+            SwapAsset::Currency,
+            vec![SwapHandler::from_exchange_id(0), SwapHandler::from_asset_id(0)],  // repeated exchange
+            100,
+            1,
+            100,
+            BOB
+        ), Error::<Test>::InvalidPath);
+
+        assert_noop!(DexModule::swap_exact_in_by_path(
+            Origin::signed(BOB),
+            SwapAsset::Currency,
+            path.clone(),
+            100,
+            83,     // min output set too high
+            100,
+            BOB
+        ), Error::<Test>::NotEnoughOutput);
+
+        // Currency -> token 0 -> token 1
+        assert_ok!(DexModule::swap_exact_in_by_path(
+            Origin::signed(BOB),
+            SwapAsset::Currency,
+            path,
+            100,
+            82,
+            100,
+            BOB
+        ));
+
+        assert_eq!(Currency::free_balance(BOB), 10000 - 100);
+        assert_eq!(TokenModule::balance_of(&0, &BOB), 0);
+        assert_eq!(TokenModule::balance_of(&1, &BOB), 82);
+        assert_eq!(Currency::free_balance(EXCHANGE_ACCOUNT), 1000 + 100);
+        assert_eq!(TokenModule::balance_of(&0, &EXCHANGE_ACCOUNT), 1000 - 90);
+        assert_eq!(TokenModule::balance_of(&0, &EXCHANGE_ACCOUNT2), 1000 + 90);
+        assert_eq!(TokenModule::balance_of(&1, &EXCHANGE_ACCOUNT2), 1000 - 82);
+
+        // Token 1 -> token 0 -> currency
+        let path = vec![SwapHandler::from_exchange_id(1), SwapHandler::from_asset_id(0)];
+
+        assert_noop!(DexModule::swap_exact_out_by_path(
+            Origin::signed(BOB),
+            SwapAsset::Token(1),
+            path.clone(),
+            50,
+            38,     // max input set too low
+            100,
+            BOB
+        ), Error::<Test>::TooExpensiveInput);
+
+        assert_ok!(DexModule::swap_exact_out_by_path(
+            Origin::signed(BOB),
+            SwapAsset::Token(1),
+            path,
+            50,
+            39,
+            100,
+            BOB
+        ));
+
+        assert_eq!(Currency::free_balance(BOB), 10000 - 100 + 50);
+        assert_eq!(TokenModule::balance_of(&1, &BOB), 82 - 39);
+        assert_eq!(TokenModule::balance_of(&0, &BOB), 0);
+        assert_eq!(Currency::free_balance(EXCHANGE_ACCOUNT), 1100 - 50);
+        assert_eq!(TokenModule::balance_of(&0, &EXCHANGE_ACCOUNT), 910 + 44);
+        assert_eq!(TokenModule::balance_of(&0, &EXCHANGE_ACCOUNT2), 1090 - 44);
+        assert_eq!(TokenModule::balance_of(&1, &EXCHANGE_ACCOUNT2), 918 + 39);
+    })
+}
+
+#[test]
+fn liquidity_and_swap_should_not_use_allowance() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(TokenModule::inner_issue(&ALICE, 1000, TEST_TOKEN), 0);
+        assert_ok!(TokenModule::inner_transfer(&0, &ALICE, &BOB, 100));
+        assert_ok!(DexModule::create_exchange(Origin::signed(ALICE), 0));
+        assert_ok!(TokenModule::inner_approve(&0, &BOB, &EXCHANGE_ACCOUNT, 30));
+
+        // No allowance is needed to add liquidity
+        assert_eq!(TokenModule::allowances(&0, &ALICE, &EXCHANGE_ACCOUNT), 0);
+        assert_ok!(DexModule::add_liquidity(
+            Origin::signed(ALICE),
+            SwapHandler::from_exchange_id(0),
+            1000,
+            0,
+            900,
+            100
+        ));
+        assert_eq!(TokenModule::balance_of(&0, &EXCHANGE_ACCOUNT), 900);
+        assert_eq!(TokenModule::allowances(&0, &ALICE, &EXCHANGE_ACCOUNT), 0);
+
+        // The existing allowance is left unchanged by the swap
+        assert_ok!(DexModule::token_to_currency_input(
+            Origin::signed(BOB),
+            SwapHandler::from_exchange_id(0),
+            50,
+            1,
+            100,
+            BOB
+        ));
+        assert_eq!(TokenModule::balance_of(&0, &BOB), 50);
+        assert_eq!(TokenModule::balance_of(&0, &EXCHANGE_ACCOUNT), 950);
+        assert_eq!(TokenModule::allowances(&0, &BOB, &EXCHANGE_ACCOUNT), 30);
+    })
+}
+
+#[test]
+fn add_liquidity_should_roll_back() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(TokenModule::inner_issue(&ALICE, 1000, TEST_TOKEN), 0);
+        assert_ok!(DexModule::create_exchange(Origin::signed(ALICE), 0));
+
+        // Add 100 currency and 500 token
+        assert_ok!(DexModule::add_liquidity(
+            Origin::signed(ALICE),
+            SwapHandler::from_exchange_id(0),
+            100,
+            0,
+            500,
+            100
+        ));
+
+        // The currency is transferred and the liquidity is minted, then the token transfer fails
+        assert_noop!(DexModule::add_liquidity(
+            Origin::signed(ALICE),
+            SwapHandler::from_exchange_id(0),
+            200,
+            1,
+            2000,
+            100
+        ), zenlink_assets::Error::<Test>::BalanceLow);
+
+        assert_eq!(Currency::free_balance(ALICE), 10000 - 100);
+        assert_eq!(Currency::free_balance(EXCHANGE_ACCOUNT), 100);
+        assert_eq!(TokenModule::balance_of(&0, &ALICE), 500);
+        assert_eq!(TokenModule::balance_of(&0, &EXCHANGE_ACCOUNT), 500);
+        assert_eq!(TokenModule::total_supply(&1), 100);
+        assert_eq!(TokenModule::balance_of(&1, &ALICE), 100);
+
+        let exchange = DexModule::get_exchange_info(0).unwrap();
+        assert_eq!(exchange.currency_reserve, 100);
+        assert_eq!(exchange.token_reserve, 500);
+    })
+}
+
+#[test]
+fn remove_liquidity_should_roll_back() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(TokenModule::inner_issue(&ALICE, 1, TEST_TOKEN), 0);
+        assert_ok!(DexModule::create_exchange(Origin::signed(ALICE), 0));
+
+        // Add 1000 currency and 1 token
+        assert_ok!(DexModule::add_liquidity(
+            Origin::signed(ALICE),
+            SwapHandler::from_exchange_id(0),
+            1000,
+            0,
+            1,
+            100
+        ));
+
+        // The liquidity is burned and the currency is transferred, then the zero token transfer fails
+        assert_noop!(DexModule::remove_liquidity(
+            Origin::signed(ALICE),
+            SwapHandler::from_exchange_id(0),
+            100,
+            1,
+            0,
+            100
+        ), zenlink_assets::Error::<Test>::AmountZero);
+
+        assert_eq!(Currency::free_balance(ALICE), 10000 - 1000);
+        assert_eq!(Currency::free_balance(EXCHANGE_ACCOUNT), 1000);
+        assert_eq!(TokenModule::balance_of(&0, &EXCHANGE_ACCOUNT), 1);
+        assert_eq!(TokenModule::total_supply(&1), 1000);
+        assert_eq!(TokenModule::balance_of(&1, &ALICE), 1000);
+
+        let exchange = DexModule::get_exchange_info(0).unwrap();
+        assert_eq!(exchange.currency_reserve, 1000);
+        assert_eq!(exchange.token_reserve, 1);
+    })
+}
+
+#[test]
+fn token_to_currency_input_should_roll_back() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(TokenModule::inner_issue(&ALICE, 42*2, TEST_TOKEN), 0);
+        assert_ok!(TokenModule::inner_transfer(&0, &ALICE, &BOB, 42));
+        assert_ok!(DexModule::create_exchange(Origin::signed(ALICE), 0));
+
+        // Add 420 currency and 42 token
+        assert_ok!(DexModule::add_liquidity(
+            Origin::signed(ALICE),
+            SwapHandler::from_exchange_id(0),
+            420,
+            0,
+            42,
+            100
+        ));
+
+        let total_supply = TokenModule::total_supply(&1);
+
+        // The currency is transferred to BOB, then the token transfer fails
+        assert_noop!(DexModule::token_to_currency_input(
+            Origin::signed(BOB),
+            SwapHandler::from_exchange_id(0),
+            50,
+            1,
+            100,
+            BOB
+        ), zenlink_assets::Error::<Test>::BalanceLow);
+
+        assert_eq!(Currency::free_balance(BOB), 10000);
+        assert_eq!(Currency::free_balance(EXCHANGE_ACCOUNT), 420);
+        assert_eq!(TokenModule::balance_of(&0, &BOB), 42);
+        assert_eq!(TokenModule::balance_of(&0, &EXCHANGE_ACCOUNT), 42);
+        assert_eq!(TokenModule::total_supply(&1), total_supply);
+
+        let exchange = DexModule::get_exchange_info(0).unwrap();
+        assert_eq!(exchange.currency_reserve, 420);
+        assert_eq!(exchange.token_reserve, 42);
+    })
+}
+
+#[test]
+fn add_pair_liquidity_should_roll_back() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(TokenModule::inner_issue(&ALICE, 10000, TEST_TOKEN), 0);
+        assert_eq!(TokenModule::inner_issue(&ALICE, 100, TEST_OTHER_TOKEN), 1);
+        assert_ok!(DexModule::create_pair(Origin::signed(ALICE), 0, 1));
+
+        // The token is transferred, then the other token transfer fails
+        assert_noop!(DexModule::add_pair_liquidity(
+            Origin::signed(ALICE),
+            0,
+            1,
+            1000,
+            0,
+            1000,
+            100
+        ), zenlink_assets::Error::<Test>::BalanceLow);
+
+        assert_eq!(TokenModule::balance_of(&0, &ALICE), 10000);
+        assert_eq!(TokenModule::balance_of(&1, &ALICE), 100);
+        assert_eq!(TokenModule::balance_of(&0, &EXCHANGE_ACCOUNT), 0);
+        assert_eq!(TokenModule::total_supply(&2), 0);
+
+        let pair = DexModule::get_pair(0).unwrap();
+        assert_eq!(pair.reserve_0, 0);
+        assert_eq!(pair.reserve_1, 0);
+    })
+}
+
+#[test]
+fn currency_to_token_should_roll_back() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(TokenModule::inner_issue(&ALICE, 1000, TEST_TOKEN), 0);
+        assert_ok!(DexModule::create_exchange(Origin::signed(ALICE), 0));
+
+        // Add 1000 currency and 1000 token
+        assert_ok!(DexModule::add_liquidity(
+            Origin::signed(ALICE),
+            SwapHandler::from_exchange_id(0),
+            1000,
+            0,
+            1000,
+            100
+        ));
+
+        // Drain the token of the exchange account behind its reserves
+        assert_ok!(TokenModule::inner_transfer(&0, &EXCHANGE_ACCOUNT, &CHAREL, 1000));
+        let total_supply = TokenModule::total_supply(&1);
+
+        // The currency is transferred, then the token transfer fails
+        assert_noop!(DexModule::currency_to_token_input(
+            Origin::signed(BOB),
+            SwapHandler::from_exchange_id(0),
+            100,
+            1,
+            100,
+            BOB
+        ), zenlink_assets::Error::<Test>::BalanceLow);
+
+        assert_noop!(DexModule::currency_to_token_output(
+            Origin::signed(BOB),
+            SwapHandler::from_exchange_id(0),
+            50,
+            100,
+            100,
+            BOB
+        ), zenlink_assets::Error::<Test>::BalanceLow);
+
+        assert_eq!(Currency::free_balance(BOB), 10000);
+        assert_eq!(Currency::free_balance(EXCHANGE_ACCOUNT), 1000);
+        assert_eq!(TokenModule::balance_of(&0, &BOB), 0);
+        assert_eq!(TokenModule::total_supply(&1), total_supply);
+
+        let exchange = DexModule::get_exchange_info(0).unwrap();
+        assert_eq!(exchange.currency_reserve, 1000);
+        assert_eq!(exchange.token_reserve, 1000);
+    })
+}
+
+#[test]
+fn token_to_token_should_roll_back() {
+    new_test_ext().execute_with(|| {
+        // The exchange 0 of token 0, the exchange 1 of token 2
+        assert_eq!(TokenModule::inner_issue(&ALICE, 1100, TEST_TOKEN), 0);
+        assert_ok!(DexModule::create_exchange(Origin::signed(ALICE), 0));
+        assert_ok!(DexModule::add_liquidity(
+            Origin::signed(ALICE),
+            SwapHandler::from_exchange_id(0),
+            1000,
+            0,
+            1000,
+            100
+        ));
+        assert_eq!(TokenModule::inner_issue(&BOB, 1000, TEST_OTHER_TOKEN), 2);
+        assert_ok!(DexModule::create_exchange(Origin::signed(BOB), 2));
+        assert_ok!(DexModule::add_liquidity(
+            Origin::signed(BOB),
+            SwapHandler::from_exchange_id(1),
+            1000,
+            0,
+            1000,
+            100
+        ));
+        assert_ok!(TokenModule::inner_transfer(&0, &ALICE, &CHAREL, 100));
+
+        // Drain the other token of the other exchange account behind its reserves
+        assert_ok!(TokenModule::inner_transfer(&2, &EXCHANGE_ACCOUNT2, &BOB, 1000));
+        let total_supply = TokenModule::total_supply(&1);
+        let other_total_supply = TokenModule::total_supply(&3);
+
+        // The token and the currency are transferred, then the other token transfer fails
+        assert_noop!(DexModule::token_to_token_input(
+            Origin::signed(CHAREL),
+            SwapHandler::from_asset_id(0),
+            SwapHandler::from_asset_id(2),
+            100,
+            1,
+            100,
+            CHAREL
+        ), zenlink_assets::Error::<Test>::BalanceLow);
+
+        assert_noop!(DexModule::token_to_token_output(
+            Origin::signed(CHAREL),
+            SwapHandler::from_asset_id(0),
+            SwapHandler::from_asset_id(2),
+            50,
+            100,
+            100,
+            CHAREL
+        ), zenlink_assets::Error::<Test>::BalanceLow);
+
+        assert_eq!(TokenModule::balance_of(&0, &CHAREL), 100);
+        assert_eq!(TokenModule::balance_of(&0, &EXCHANGE_ACCOUNT), 1000);
+        assert_eq!(Currency::free_balance(EXCHANGE_ACCOUNT), 1000);
+        assert_eq!(Currency::free_balance(EXCHANGE_ACCOUNT2), 1000);
+        assert_eq!(TokenModule::total_supply(&1), total_supply);
+        assert_eq!(TokenModule::total_supply(&3), other_total_supply);
+
+        let exchange = DexModule::get_exchange_info(0).unwrap();
+        assert_eq!(exchange.currency_reserve, 1000);
+        assert_eq!(exchange.token_reserve, 1000);
+        let other_exchange = DexModule::get_exchange_info(1).unwrap();
+        assert_eq!(other_exchange.currency_reserve, 1000);
+        assert_eq!(other_exchange.token_reserve, 1000);
+    })
+}
+
+#[test]
+fn pair_swap_should_roll_back() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(TokenModule::inner_issue(&ALICE, 10000, TEST_TOKEN), 0);
+        assert_eq!(TokenModule::inner_issue(&ALICE, 10000, TEST_OTHER_TOKEN), 1);
+        assert_ok!(DexModule::create_pair(Origin::signed(ALICE), 0, 1));
+
+        // Add 1000 token and 1000 other token
+        assert_ok!(DexModule::add_pair_liquidity(
+            Origin::signed(ALICE),
+            0,
+            1,
+            1000,
+            0,
+            1000,
+            100
+        ));
+        assert_ok!(TokenModule::inner_transfer(&0, &ALICE, &BOB, 100));
+
+        // Drain the other token of the pair account behind its reserves
+        assert_ok!(TokenModule::inner_transfer(&1, &EXCHANGE_ACCOUNT, &CHAREL, 1000));
+        let total_supply = TokenModule::total_supply(&2);
+
+        // The token is transferred, then the other token transfer fails
+        assert_noop!(DexModule::token_to_token_pair_input(
+            Origin::signed(BOB),
+            0,
+            1,
+            100,
+            1,
+            100,
+            BOB
+        ), zenlink_assets::Error::<Test>::BalanceLow);
+
+        assert_noop!(DexModule::token_to_token_pair_output(
+            Origin::signed(BOB),
+            0,
+            1,
+            50,
+            100,
+            100,
+            BOB
+        ), zenlink_assets::Error::<Test>::BalanceLow);
+
+        assert_eq!(TokenModule::balance_of(&0, &BOB), 100);
+        assert_eq!(TokenModule::balance_of(&0, &EXCHANGE_ACCOUNT), 1000);
+        assert_eq!(TokenModule::total_supply(&2), total_supply);
+
+        let pair = DexModule::get_pair(0).unwrap();
+        assert_eq!(pair.reserve_0, 1000);
+        assert_eq!(pair.reserve_1, 1000);
+    })
+}
+
+#[test]
+fn swap_by_path_should_roll_back() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(TokenModule::inner_issue(&ALICE, 10000, TEST_TOKEN), 0);
+        assert_eq!(TokenModule::inner_issue(&ALICE, 10000, TEST_OTHER_TOKEN), 1);
+
+        // The exchange 0 of currency and token 0, the pair 1 of token 0 and token 1
+        assert_ok!(DexModule::create_exchange(Origin::signed(ALICE), 0));
+        assert_ok!(DexModule::create_pair(Origin::signed(ALICE), 0, 1));
+        assert_ok!(DexModule::add_liquidity(
+            Origin::signed(ALICE),
+            SwapHandler::from_exchange_id(0),
+            1000,
+            0,
+            1000,
+            100
+        ));
+        assert_ok!(DexModule::add_pair_liquidity(
+            Origin::signed(ALICE),
+            0,
+            1,
+            1000,
+            0,
+            1000,
+            100
+        ));
+
+        // Drain the token 1 of the pair account behind its reserves
+        assert_ok!(TokenModule::inner_transfer(&1, &EXCHANGE_ACCOUNT2, &CHAREL, 1000));
+        let total_supply = TokenModule::total_supply(&2);
+        let pair_total_supply = TokenModule::total_supply(&3);
+
+        let path = vec![SwapHandler::from_exchange_id(0), SwapHandler::from_exchange_id(1)];
+
+        // The first hop is swapped, then the token 1 transfer of the last hop fails
+        assert_noop!(DexModule::swap_exact_in_by_path(
+            Origin::signed(BOB),
+            SwapAsset::Currency,
+            path.clone(),
+            100,
+            1,
+            100,
+            BOB
+        ), zenlink_assets::Error::<Test>::BalanceLow);
+
+        assert_noop!(DexModule::swap_exact_out_by_path(
+            Origin::signed(BOB),
+            SwapAsset::Currency,
+            path,
+            50,
+            1000,
+            100,
+            BOB
+        ), zenlink_assets::Error::<Test>::BalanceLow);
+
+        assert_eq!(Currency::free_balance(BOB), 10000);
+        assert_eq!(Currency::free_balance(EXCHANGE_ACCOUNT), 1000);
+        assert_eq!(TokenModule::balance_of(&0, &EXCHANGE_ACCOUNT), 1000);
+        assert_eq!(TokenModule::balance_of(&0, &EXCHANGE_ACCOUNT2), 1000);
+        assert_eq!(TokenModule::total_supply(&2), total_supply);
+        assert_eq!(TokenModule::total_supply(&3), pair_total_supply);
+
+        let exchange = DexModule::get_exchange_info(0).unwrap();
+        assert_eq!(exchange.currency_reserve, 1000);
+        assert_eq!(exchange.token_reserve, 1000);
+        let pair = DexModule::get_pair(1).unwrap();
+        assert_eq!(pair.reserve_0, 1000);
+        assert_eq!(pair.reserve_1, 1000);
+    })
+}
+
+#[test]
+fn large_reserves_should_not_overflow() {
+    new_test_ext().execute_with(|| {
+        let amount: u64 = 1 << 40;
+        assert_eq!(TokenModule::inner_issue(&ALICE, u64::max_value(), TEST_TOKEN), 0);
+        assert_eq!(TokenModule::inner_issue(&ALICE, u64::max_value(), TEST_OTHER_TOKEN), 1);
+        assert_ok!(DexModule::create_pair(Origin::signed(ALICE), 0, 1));
+
+        // amount * amount overflows u64, the liquidity is sqrt(amount * amount)
+        assert_ok!(DexModule::add_pair_liquidity(
+            Origin::signed(ALICE),
+            0,
+            1,
+            amount,
+            0,
+            amount,
+            100
+        ));
+        assert_eq!(TokenModule::total_supply(&2), amount);
+
+        // amount * 997_000 * amount / (amount * 1_000_000 + amount * 997_000)
+        assert_ok!(DexModule::token_to_token_pair_input(
+            Origin::signed(ALICE),
+            0,
+            1,
+            amount,
+            1,
+            100,
+            BOB
+        ));
+        assert_eq!(TokenModule::balance_of(&1, &BOB), 548929941358);
+
+        assert_noop!(DexModule::token_to_token_pair_output(
+            Origin::signed(ALICE),
+            0,
+            1,
+            amount, // more than the reserve
+            u64::max_value(),
+            100,
+            BOB
+        ), Error::<Test>::InsufficientReserve);
+    })
+}
+
+#[test]
+fn liquidity_overflow_should_not_work() {
+    new_test_ext().execute_with(|| {
+        let currency_amount: u128 = 1 << 65;
+        Currency::make_free_balance_be(&ALICE, currency_amount * 2);
+        assert_eq!(TokenModule::inner_issue(&ALICE, 1000, TEST_TOKEN), 0);
+        assert_ok!(DexModule::create_exchange(Origin::signed(ALICE), 0));
+
+        // The initial liquidity doesn't fit in u64 token balance
+        assert_noop!(DexModule::add_liquidity(
+            Origin::signed(ALICE),
+            SwapHandler::from_exchange_id(0),
+            currency_amount,
+            0,
+            1000,
+            100
+        ), Error::<Test>::Overflow);
+    })
+}
+
+#[test]
+fn sync_and_skim_should_work() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(TokenModule::inner_issue(&ALICE, 10000, TEST_TOKEN), 0);
+        assert_ok!(DexModule::create_exchange(Origin::signed(ALICE), 0));
+
+        // Add 1000 currency and 1000 token
+        assert_ok!(DexModule::add_liquidity(
+            Origin::signed(ALICE),
+            SwapHandler::from_exchange_id(0),
+            1000,
+            0,
+            1000,
+            100
+        ));
+
+        // Donations to the exchange account don't change the reserves and the price
+        assert_ok!(Currency::transfer(Origin::signed(BOB), EXCHANGE_ACCOUNT, 500));
+        assert_ok!(TokenModule::inner_transfer(&0, &ALICE, &EXCHANGE_ACCOUNT, 500));
+
+        let exchange = DexModule::get_exchange_info(0).unwrap();
+        assert_eq!(exchange.currency_reserve, 1000);
+        assert_eq!(exchange.token_reserve, 1000);
+        assert_eq!(DexModule::get_currency_to_token_input_price(&exchange, 1000), Ok(499));
+
+        assert_ok!(DexModule::skim(
+            Origin::signed(BOB),
+            SwapHandler::from_exchange_id(0),
+            CHAREL
+        ));
+        assert_eq!(Currency::free_balance(CHAREL), 10000 + 500);
+        assert_eq!(TokenModule::balance_of(&0, &CHAREL), 500);
+        assert_eq!(Currency::free_balance(EXCHANGE_ACCOUNT), 1000);
+        assert_eq!(TokenModule::balance_of(&0, &EXCHANGE_ACCOUNT), 1000);
+
+        assert_ok!(Currency::transfer(Origin::signed(BOB), EXCHANGE_ACCOUNT, 500));
+        assert_ok!(TokenModule::inner_transfer(&0, &ALICE, &EXCHANGE_ACCOUNT, 500));
+
+        assert_noop!(
+            DexModule::sync(Origin::signed(BOB), SwapHandler::from_exchange_id(1)),
+            Error::<Test>::ExchangeNotExists
+        );
+        assert_ok!(DexModule::sync(Origin::signed(BOB), SwapHandler::from_asset_id(0)));
+
+        let exchange = DexModule::get_exchange_info(0).unwrap();
+        assert_eq!(exchange.currency_reserve, 1500);
+        assert_eq!(exchange.token_reserve, 1500);
+    })
+}
+
+#[test]
+fn minimum_liquidity_should_be_locked() {
+    new_test_ext().execute_with(|| {
+        set_minimum_liquidity(1000);
+        let locked = DexModule::minimum_liquidity_account();
+
+        assert_eq!(TokenModule::inner_issue(&ALICE, 10000, TEST_TOKEN), 0);
+        assert_ok!(DexModule::create_exchange(Origin::signed(ALICE), 0));
+
+        assert_noop!(DexModule::add_liquidity(
+            Origin::signed(ALICE),
+            SwapHandler::from_exchange_id(0),
+            1000,   // not more than the minimum liquidity
+            0,
+            1000,
+            100
+        ), Error::<Test>::BelowMinimumLiquidity);
+
+        assert_ok!(DexModule::add_liquidity(
+            Origin::signed(ALICE),
+            SwapHandler::from_exchange_id(0),
+            5000,
+            0,
+            5000,
+            100
+        ));
+        assert_eq!(TokenModule::balance_of(&1, &ALICE), 4000);
+        assert_eq!(TokenModule::balance_of(&1, &locked), 1000);
+        assert_eq!(TokenModule::total_supply(&1), 5000);
+
+        // The exchange can't be drained
+        assert_ok!(DexModule::remove_liquidity(
+            Origin::signed(ALICE),
+            SwapHandler::from_exchange_id(0),
+            4000,
+            1,
+            1,
+            100
+        ));
+        assert_eq!(TokenModule::total_supply(&1), 1000);
+        let exchange = DexModule::get_exchange_info(0).unwrap();
+        assert_eq!(exchange.currency_reserve, 1000);
+        assert_eq!(exchange.token_reserve, 1000);
+
+        assert_eq!(TokenModule::inner_issue(&ALICE, 10000, TEST_OTHER_TOKEN), 2);
+        assert_ok!(DexModule::create_pair(Origin::signed(ALICE), 0, 2));
+
+        assert_noop!(DexModule::add_pair_liquidity(
+            Origin::signed(ALICE),
+            0,
+            2,
+            1000,
+            0,
+            1000,
+            100
+        ), Error::<Test>::BelowMinimumLiquidity);
+
+        assert_ok!(DexModule::add_pair_liquidity(
+            Origin::signed(ALICE),
+            0,
+            2,
+            2000,
+            0,
+            2000,
+            100
+        ));
+        assert_eq!(TokenModule::balance_of(&3, &ALICE), 1000);
+        assert_eq!(TokenModule::balance_of(&3, &locked), 1000);
+    })
+}
+
+#[test]
+fn consult_average_price_should_work() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        assert_eq!(TokenModule::inner_issue(&ALICE, 10000, TEST_TOKEN), 0);
+        assert_ok!(DexModule::create_exchange(Origin::signed(ALICE), 0));
+
+        // Add 100 currency and 1000 token at block 1
+        assert_ok!(DexModule::add_liquidity(
+            Origin::signed(ALICE),
+            SwapHandler::from_exchange_id(0),
+            100,
+            0,
+            1000,
+            100
+        ));
+
+        // Sell 100 currency for 499 token at block 11
+        System::set_block_number(11);
+        assert_ok!(DexModule::currency_to_token_input(
+            Origin::signed(BOB),
+            SwapHandler::from_exchange_id(0),
+            100,
+            1,
+            100,
+            BOB
+        ));
+
+        // The price of blocks 1..11 is 1000 / 100, the trade in block 11 doesn't count yet
+        assert_eq!(
+            DexModule::consult(0, 10),
+            Ok((FixedU128::saturating_from_integer(10), FixedU128::saturating_from_rational(1, 10)))
+        );
+
+        // The price of blocks 11..21 is 501 / 200
+        System::set_block_number(21);
+        assert_eq!(
+            DexModule::consult(0, 10),
+            Ok((FixedU128::saturating_from_rational(501, 200), FixedU128::from_inner(399201596806387225)))
+        );
+        // The average over blocks 1..21
+        assert_eq!(
+            DexModule::consult(0, 20),
+            Ok((FixedU128::from_inner(6252500000000000000), FixedU128::from_inner(249600798403193612)))
+        );
+
+        assert_eq!(DexModule::consult(0, 30), Err(Error::<Test>::NoPriceObservation));
+        assert_eq!(DexModule::consult(0, 0), Err(Error::<Test>::InvalidWindow));
+        assert_eq!(DexModule::consult(1, 10), Err(Error::<Test>::ExchangeNotExists));
+    })
+}
+
+#[test]
+fn flash_swap_should_work() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(TokenModule::inner_issue(&ALICE, 10000, TEST_TOKEN), 0);
+        assert_ok!(DexModule::create_exchange(Origin::signed(ALICE), 0));
+        assert_ok!(TokenModule::inner_transfer(&0, &ALICE, &BOB, 1000));
+
+        // Add 1000 currency and 1000 token
+        assert_ok!(DexModule::add_liquidity(
+            Origin::signed(ALICE),
+            SwapHandler::from_exchange_id(0),
+            1000,
+            0,
+            1000,
+            100
+        ));
+
+        assert_noop!(
+            DexModule::flash_swap(Origin::signed(ALICE), SwapHandler::from_exchange_id(0), 0, 0, BOB, (0u128, 0u64).encode()),
+            Error::<Test>::ZeroFlashSwap
+        );
+        assert_noop!(
+            DexModule::flash_swap(Origin::signed(ALICE), SwapHandler::from_exchange_id(0), 0, 1000, BOB, (0u128, 1000u64).encode()),
+            Error::<Test>::InsufficientReserve
+        );
+        // Repaying without the fee breaks the constant product
+        assert_noop!(
+            DexModule::flash_swap(Origin::signed(ALICE), SwapHandler::from_exchange_id(0), 0, 100, BOB, (0u128, 100u64).encode()),
+            Error::<Test>::FlashSwapNotRepaid
+        );
+
+        // Borrow 100 token and repay 101 token
+        assert_ok!(DexModule::flash_swap(
+            Origin::signed(ALICE),
+            SwapHandler::from_exchange_id(0),
+            0,
+            100,
+            BOB,
+            (0u128, 101u64).encode()
+        ));
+        assert_eq!(TokenModule::balance_of(&0, &BOB), 1000 - 1);
+        assert_eq!(TokenModule::balance_of(&0, &EXCHANGE_ACCOUNT), 1001);
+
+        // Borrow 100 token and repay in 112 currency
+        assert_ok!(DexModule::flash_swap(
+            Origin::signed(ALICE),
+            SwapHandler::from_asset_id(0),
+            0,
+            100,
+            BOB,
+            (112u128, 0u64).encode()
+        ));
+        assert_eq!(TokenModule::balance_of(&0, &BOB), 1000 - 1 + 100);
+        assert_eq!(Currency::free_balance(BOB), 10000 - 112);
+
+        let exchange = DexModule::get_exchange_info(0).unwrap();
+        assert_eq!(exchange.currency_reserve, 1112);
+        assert_eq!(exchange.token_reserve, 901);
+    })
+}
+
+#[test]
+fn flash_swap_reentrancy_should_not_work() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(TokenModule::inner_issue(&ALICE, 10000, TEST_TOKEN), 0);
+        assert_ok!(DexModule::create_exchange(Origin::signed(ALICE), 0));
+        assert_ok!(TokenModule::inner_transfer(&0, &ALICE, &BOB, 1000));
+
+        // Add 1000 currency and 1000 token
+        assert_ok!(DexModule::add_liquidity(
+            Origin::signed(ALICE),
+            SwapHandler::from_exchange_id(0),
+            1000,
+            0,
+            1000,
+            100
+        ));
+
+        // Repaying by adding liquidity to the exchange in the callback
+        set_flash_swap_reenter(true);
+        assert_noop!(
+            DexModule::flash_swap(Origin::signed(ALICE), SwapHandler::from_exchange_id(0), 0, 100, BOB, (200u128, 200u64).encode()),
+            Error::<Test>::Locked
+        );
+        set_flash_swap_reenter(false);
+
+        assert!(!DexModule::flash_swap_locked(0));
+        assert_ok!(DexModule::flash_swap(
+            Origin::signed(ALICE),
+            SwapHandler::from_exchange_id(0),
+            0,
+            100,
+            BOB,
+            (0u128, 101u64).encode()
+        ));
+        assert!(!DexModule::flash_swap_locked(0));
+    })
+}
+
+#[test]
+fn exchange_status_should_work() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(TokenModule::inner_issue(&ALICE, 10000, TEST_TOKEN), 0);
+        assert_ok!(DexModule::create_exchange(Origin::signed(ALICE), 0));
+
+        // Add 1000 currency and 1000 token
+        assert_ok!(DexModule::add_liquidity(
+            Origin::signed(ALICE),
+            SwapHandler::from_exchange_id(0),
+            1000,
+            0,
+            1000,
+            100
+        ));
+
+        assert_noop!(DexModule::set_exchange_status(
+            Origin::signed(ALICE),  // not the control origin
+            SwapHandler::from_exchange_id(0),
+            ExchangeStatus::SwapsPaused
+        ), DispatchError::BadOrigin);
+        assert_noop!(DexModule::set_exchange_status(
+            Origin::root(),
+            SwapHandler::from_exchange_id(1),   // no exchange
+            ExchangeStatus::SwapsPaused
+        ), Error::<Test>::ExchangeNotExists);
+        assert_noop!(
+            DexModule::set_global_status(Origin::signed(ALICE), ExchangeStatus::SwapsPaused),
+            DispatchError::BadOrigin
+        );
+
+        // Pause the swaps of the exchange, liquidity is still allowed
+        assert_ok!(DexModule::set_exchange_status(
+            Origin::root(),
+            SwapHandler::from_exchange_id(0),
+            ExchangeStatus::SwapsPaused
+        ));
+        assert_eq!(DexModule::effective_status(0), ExchangeStatus::SwapsPaused);
+        assert_noop!(
+            DexModule::currency_to_token_input(Origin::signed(ALICE), SwapHandler::from_exchange_id(0), 100, 1, 100, ALICE),
+            Error::<Test>::SwapsPaused
+        );
+        assert_ok!(DexModule::add_liquidity(Origin::signed(ALICE), SwapHandler::from_exchange_id(0), 100, 0, 1000, 100));
+
+        // Only withdraw from the exchange
+        assert_ok!(DexModule::set_exchange_status(
+            Origin::root(),
+            SwapHandler::from_exchange_id(0),
+            ExchangeStatus::WithdrawOnly
+        ));
+        assert_noop!(
+            DexModule::add_liquidity(Origin::signed(ALICE), SwapHandler::from_exchange_id(0), 100, 0, 1000, 100),
+            Error::<Test>::WithdrawOnly
+        );
+        assert_noop!(
+            DexModule::token_to_currency_input(Origin::signed(ALICE), SwapHandler::from_exchange_id(0), 100, 1, 100, ALICE),
+            Error::<Test>::WithdrawOnly
+        );
+        assert_ok!(DexModule::remove_liquidity(Origin::signed(ALICE), SwapHandler::from_exchange_id(0), 100, 1, 1, 100));
+
+        // The stricter one of the global status and the exchange status applies
+        assert_ok!(DexModule::set_exchange_status(
+            Origin::root(),
+            SwapHandler::from_exchange_id(0),
+            ExchangeStatus::Active
+        ));
+        assert_ok!(DexModule::set_global_status(Origin::root(), ExchangeStatus::SwapsPaused));
+        assert_eq!(DexModule::effective_status(0), ExchangeStatus::SwapsPaused);
+        assert_noop!(
+            DexModule::currency_to_token_input(Origin::signed(ALICE), SwapHandler::from_exchange_id(0), 100, 1, 100, ALICE),
+            Error::<Test>::SwapsPaused
+        );
+
+        assert_ok!(DexModule::set_global_status(Origin::root(), ExchangeStatus::Active));
+        assert_ok!(DexModule::currency_to_token_input(
+            Origin::signed(ALICE),
+            SwapHandler::from_exchange_id(0),
+            100,
+            1,
+            100,
+            ALICE
+        ));
+    });
+}
+
+#[test]
+fn add_liquidity_by_amounts_should_work() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(TokenModule::inner_issue(&ALICE, 10000, TEST_TOKEN), 0);
+        assert_ok!(DexModule::create_exchange(Origin::signed(ALICE), 0));
+        assert_ok!(TokenModule::inner_transfer(&0, &ALICE, &BOB, 1000));
+
+        // The fresh exchange takes the desired amounts
+        assert_ok!(DexModule::add_liquidity_by_amounts(
+            Origin::signed(ALICE),
+            SwapHandler::from_exchange_id(0),
+            1000,
+            2000,
+            1000,
+            2000,
+            100
+        ));
+        let exchange = DexModule::get_exchange_info(0).unwrap();
+        assert_eq!(exchange.currency_reserve, 1000);
+        assert_eq!(exchange.token_reserve, 2000);
+        assert_eq!(TokenModule::balance_of(&1, &ALICE), 1000);
+
+        assert_noop!(DexModule::add_liquidity_by_amounts(
+            Origin::signed(BOB),
+            SwapHandler::from_exchange_id(0),
+            100,
+            300,
+            1,
+            250,    // only 200 token is in proportion
+            100
+        ), Error::<Test>::NotEnoughToken);
+
+        // Deposit all the currency and 200 of 300 token
+        assert_ok!(DexModule::add_liquidity_by_amounts(
+            Origin::signed(BOB),
+            SwapHandler::from_exchange_id(0),
+            100,
+            300,
+            1,
+            1,
+            100
+        ));
+        assert_eq!(Currency::free_balance(&BOB), 10000 - 100);
+        assert_eq!(TokenModule::balance_of(&0, &BOB), 1000 - 200);
+        assert_eq!(TokenModule::balance_of(&1, &BOB), 100);
+
+        assert_noop!(DexModule::add_liquidity_by_amounts(
+            Origin::signed(BOB),
+            SwapHandler::from_exchange_id(0),
+            100,
+            100,
+            60,     // only 50 currency is in proportion
+            1,
+            100
+        ), Error::<Test>::NotEnoughCurrency);
+
+        // Deposit 50 of 100 currency and all the token
+        assert_ok!(DexModule::add_liquidity_by_amounts(
+            Origin::signed(BOB),
+            SwapHandler::from_exchange_id(0),
+            100,
+            100,
+            1,
+            1,
+            100
+        ));
+        assert_eq!(Currency::free_balance(&BOB), 10000 - 100 - 50);
+        assert_eq!(TokenModule::balance_of(&0, &BOB), 1000 - 200 - 100);
+        assert_eq!(TokenModule::balance_of(&1, &BOB), 100 + 50);
+
+        let exchange = DexModule::get_exchange_info(0).unwrap();
+        assert_eq!(exchange.currency_reserve, 1150);
+        assert_eq!(exchange.token_reserve, 2300);
+    });
+}
+
+#[test]
+fn single_sided_liquidity_should_work() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(TokenModule::inner_issue(&ALICE, 10000, TEST_TOKEN), 0);
+        assert_ok!(DexModule::create_exchange(Origin::signed(ALICE), 0));
+
+        assert_noop!(
+            DexModule::add_liquidity_single_sided(Origin::signed(BOB), SwapHandler::from_exchange_id(0), SwapAsset::Currency, 100, 1, 100),
+            Error::<Test>::NoLiquidity
+        );
+
+        // Add 1000 currency and 1000 token
+        assert_ok!(DexModule::add_liquidity(
+            Origin::signed(ALICE),
+            SwapHandler::from_exchange_id(0),
+            1000,
+            0,
+            1000,
+            100
+        ));
+
+        assert_noop!(
+            DexModule::add_liquidity_single_sided(Origin::signed(BOB), SwapHandler::from_exchange_id(0), SwapAsset::Token(1), 100, 1, 100),
+            Error::<Test>::InvalidAsset
+        );
+        assert_noop!(
+            DexModule::add_liquidity_single_sided(Origin::signed(BOB), SwapHandler::from_exchange_id(0), SwapAsset::Currency, 100, 48, 100),
+            Error::<Test>::TooLowLiquidity
+        );
+
+        // Bob swaps 48 of 100 currency to 45 token, and deposits the rest
+        assert_ok!(DexModule::add_liquidity_single_sided(
+            Origin::signed(BOB),
+            SwapHandler::from_exchange_id(0),
+            SwapAsset::Currency,
+            100,
+            47,
+            100
+        ));
+        assert_eq!(TokenModule::balance_of(&1, &BOB), 47);
+        assert_eq!(Currency::free_balance(&BOB), 10000 - 100);
+        let exchange = DexModule::get_exchange_info(0).unwrap();
+        assert_eq!(exchange.currency_reserve, 1100);
+        assert_eq!(exchange.token_reserve, 1000);
+
+        assert_noop!(
+            DexModule::remove_liquidity_single_sided(Origin::signed(BOB), SwapHandler::from_exchange_id(0), 47, SwapAsset::Currency, 96, 100),
+            Error::<Test>::NotEnoughOutput
+        );
+
+        // Bob withdraws 49 currency and 44 token, and swaps the token to 46 currency
+        assert_ok!(DexModule::remove_liquidity_single_sided(
+            Origin::signed(BOB),
+            SwapHandler::from_exchange_id(0),
+            47,
+            SwapAsset::Currency,
+            95,
+            100
+        ));
+        assert_eq!(TokenModule::balance_of(&1, &BOB), 0);
+        assert_eq!(TokenModule::balance_of(&0, &BOB), 0);
+        assert_eq!(Currency::free_balance(&BOB), 10000 - 100 + 95);
+        let exchange = DexModule::get_exchange_info(0).unwrap();
+        assert_eq!(exchange.currency_reserve, 1005);
+        assert_eq!(exchange.token_reserve, 1000);
+        assert_eq!(Currency::free_balance(&EXCHANGE_ACCOUNT), 1005);
+
+        // Charel deposits only the token, without approving the exchange
+        assert_ok!(TokenModule::inner_transfer(&0, &ALICE, &CHAREL, 100));
+        assert_ok!(DexModule::add_liquidity_single_sided(
+            Origin::signed(CHAREL),
+            SwapHandler::from_asset_id(0),
+            SwapAsset::Token(0),
+            100,
+            1,
+            100
+        ));
+        assert_eq!(TokenModule::balance_of(&0, &CHAREL), 0);
+        assert_eq!(TokenModule::balance_of(&1, &CHAREL), 46);
+        let exchange = DexModule::get_exchange_info(0).unwrap();
+        assert_eq!(exchange.currency_reserve, 1005);
+        assert_eq!(exchange.token_reserve, 1100);
+    });
+}
+
+#[test]
+fn single_sided_swap_amount_should_check_fee_rate() {
+    new_test_ext().execute_with(|| {
+        let amount_in = U256::from(100u32);
+        let reserve_in = U256::from(1000u32);
+
+        let amount_swapped = DexModule::get_single_sided_swap_amount(amount_in, reserve_in, Permill::from_parts(3_000)).unwrap();
+        assert!(amount_swapped > U256::zero() && amount_swapped < amount_in);
+        assert!(DexModule::get_single_sided_swap_amount(amount_in, reserve_in, Permill::from_parts(999_999)).is_ok());
+
+        // No input is kept after the fee of 100%
+        assert_eq!(
+            DexModule::get_single_sided_swap_amount(amount_in, reserve_in, Permill::one()),
+            Err(Error::<Test>::InvalidFeeRate)
+        );
+    });
+}
+
+#[test]
+fn close_exchange_should_work() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(TokenModule::inner_issue(&ALICE, 10000, TEST_TOKEN), 0);
+        assert_ok!(DexModule::create_exchange(Origin::signed(ALICE), 0));
+
+        // Add 1000 currency and 1000 token
+        assert_ok!(DexModule::add_liquidity(
+            Origin::signed(ALICE),
+            SwapHandler::from_exchange_id(0),
+            1000,
+            0,
+            1000,
+            100
+        ));
+
+        assert_noop!(
+            DexModule::close_exchange(Origin::signed(BOB), SwapHandler::from_exchange_id(0), CHAREL),
+            Error::<Test>::LiquidityOutstanding
+        );
+        assert_noop!(
+            DexModule::close_exchange(Origin::signed(BOB), SwapHandler::from_exchange_id(1), CHAREL),
+            Error::<Test>::ExchangeNotExists
+        );
+
+        // Remove all the liquidity, then leave some dust in the exchange account
+        assert_ok!(DexModule::remove_liquidity(Origin::signed(ALICE), SwapHandler::from_exchange_id(0), 1000, 1, 1, 100));
+        assert_ok!(<Currency as frame_support::traits::Currency<u128>>::transfer(
+            &ALICE,
+            &EXCHANGE_ACCOUNT,
+            5,
+            frame_support::traits::ExistenceRequirement::KeepAlive
+        ));
+        assert_ok!(TokenModule::inner_transfer(&0, &ALICE, &EXCHANGE_ACCOUNT, 7));
+
+        // Anyone could close the exchange with no liquidity held by providers
+        assert_noop!(
+            DexModule::close_exchange(Origin::root(), SwapHandler::from_asset_id(0), CHAREL),
+            DispatchError::BadOrigin
+        );
+        assert_ok!(DexModule::close_exchange(Origin::signed(BOB), SwapHandler::from_asset_id(0), CHAREL));
+        assert_eq!(Currency::free_balance(&CHAREL), 10000 + 5);
+        assert_eq!(TokenModule::balance_of(&0, &CHAREL), 7);
+        assert_eq!(Currency::free_balance(&EXCHANGE_ACCOUNT), 0);
+        assert!(DexModule::get_exchange_info(0).is_none());
+        assert_eq!(DexModule::token_to_exchange(0), None);
+        assert_eq!(DexModule::zlk_to_exchange(1), None);
+        assert_eq!(TokenModule::asset_info(&1), None);
+        assert_eq!(TokenModule::total_supply(&1), 0);
+
+        // A new exchange could be created for the token
+        assert_ok!(DexModule::create_exchange(Origin::signed(ALICE), 0));
+        assert_eq!(DexModule::token_to_exchange(0), Some(1));
+        assert_ok!(DexModule::add_liquidity(
+            Origin::signed(ALICE),
+            SwapHandler::from_exchange_id(1),
+            1000,
+            0,
+            1000,
+            100
+        ));
+
+        // Governance could close the exchange with liquidity held by providers
+        assert_noop!(
+            DexModule::force_close_exchange(Origin::signed(ALICE), SwapHandler::from_exchange_id(1), CHAREL),
+            DispatchError::BadOrigin
+        );
+        assert_ok!(DexModule::force_close_exchange(Origin::root(), SwapHandler::from_exchange_id(1), CHAREL));
+        assert_eq!(Currency::free_balance(&CHAREL), 10000 + 5 + 1000);
+        assert_eq!(TokenModule::balance_of(&0, &CHAREL), 7 + 1000);
+        assert!(DexModule::get_exchange_info(1).is_none());
+        assert_eq!(DexModule::token_to_exchange(0), None);
+    });
+}
+
+#[test]
+fn close_pair_should_work() {
+    new_test_ext().execute_with(|| {
+        set_minimum_liquidity(100);
+        let locked = DexModule::minimum_liquidity_account();
+
+        assert_eq!(TokenModule::inner_issue(&ALICE, 10000, TEST_TOKEN), 0);
+        assert_eq!(TokenModule::inner_issue(&ALICE, 10000, TEST_OTHER_TOKEN), 1);
+        assert_ok!(DexModule::create_pair(Origin::signed(ALICE), 0, 1));
+
+        // Add 1000 token and 1000 other token, 100 of the liquidity is locked
+        assert_ok!(DexModule::add_pair_liquidity(
+            Origin::signed(ALICE),
+            0,
+            1,
+            1000,
+            0,
+            1000,
+            100
+        ));
+        assert_eq!(TokenModule::balance_of(&2, &locked), 100);
+        assert_eq!(TokenModule::balance_of(&2, &ALICE), 900);
+
+        assert_noop!(
+            DexModule::close_exchange(Origin::signed(BOB), SwapHandler::from_exchange_id(0), CHAREL),
+            Error::<Test>::LiquidityOutstanding
+        );
+
+        // Only the locked liquidity is left, anyone could close the pair
+        assert_ok!(DexModule::remove_pair_liquidity(Origin::signed(ALICE), 0, 1, 900, 1, 1, 100));
+        assert_ok!(DexModule::close_exchange(Origin::signed(BOB), SwapHandler::from_exchange_id(0), CHAREL));
+        assert_eq!(TokenModule::balance_of(&0, &CHAREL), 100);
+        assert_eq!(TokenModule::balance_of(&1, &CHAREL), 100);
+        assert_eq!(TokenModule::balance_of(&0, &EXCHANGE_ACCOUNT), 0);
+        assert_eq!(TokenModule::balance_of(&1, &EXCHANGE_ACCOUNT), 0);
+        assert!(DexModule::get_pair(0).is_none());
+        assert_eq!(DexModule::get_pair_id(0, 1), None);
+        assert_eq!(DexModule::zlk_to_exchange(2), None);
+        assert_eq!(TokenModule::asset_info(&2), None);
+        assert_eq!(TokenModule::total_supply(&2), 0);
+
+        // A new pair could be created for the tokens, governance could close it with liquidity
+        assert_ok!(DexModule::create_pair(Origin::signed(ALICE), 0, 1));
+        assert_eq!(DexModule::get_pair_id(0, 1), Some(1));
+        assert_ok!(DexModule::add_pair_liquidity(
+            Origin::signed(ALICE),
+            0,
+            1,
+            1000,
+            0,
+            1000,
+            100
+        ));
+        assert_noop!(
+            DexModule::force_close_exchange(Origin::signed(ALICE), SwapHandler::from_exchange_id(1), CHAREL),
+            DispatchError::BadOrigin
+        );
+        assert_ok!(DexModule::force_close_exchange(Origin::root(), SwapHandler::from_exchange_id(1), CHAREL));
+        assert_eq!(TokenModule::balance_of(&0, &CHAREL), 100 + 1000);
+        assert_eq!(TokenModule::balance_of(&1, &CHAREL), 100 + 1000);
+        assert!(DexModule::get_pair(1).is_none());
+        assert_eq!(DexModule::get_pair_id(0, 1), None);
+    });
+}
+
+#[test]
+fn genesis_exchanges_should_work() {
+    let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+    pallet_balances::GenesisConfig::<Test> {
+        balances: vec![(ALICE, 10000)],
+    }
+    .assimilate_storage(&mut t)
+    .unwrap();
+    zenlink_assets::GenesisConfig::<Test> {
+        assets: vec![(ALICE, 10000, TEST_TOKEN.clone())],
+        balances: vec![],
+        allowances: vec![],
+    }
+    .assimilate_storage(&mut t)
+    .unwrap();
+    crate::GenesisConfig::<Test> {
+        exchanges: vec![(0, 1000, 2000, ALICE)],
+    }
+    .assimilate_storage(&mut t)
+    .unwrap();
+
+    sp_io::TestExternalities::from(t).execute_with(|| {
+        let exchange = DexModule::get_exchange_info(0).unwrap();
+        assert_eq!(DexModule::token_to_exchange(0), Some(0));
+        assert_eq!(DexModule::zlk_to_exchange(exchange.liquidity_id), Some(0));
+        assert_eq!(exchange.currency_reserve, 1000);
+        assert_eq!(exchange.token_reserve, 2000);
+
+        assert_eq!(Currency::free_balance(EXCHANGE_ACCOUNT), 1000);
+        assert_eq!(TokenModule::balance_of(&0, &EXCHANGE_ACCOUNT), 2000);
+        assert_eq!(TokenModule::balance_of(&exchange.liquidity_id, &ALICE), 1000);
+        assert_eq!(TokenModule::allowances(&0, &ALICE, &EXCHANGE_ACCOUNT), 0);
+
+        assert_ok!(DexModule::currency_to_token_input(
+            Origin::signed(ALICE),
+            SwapHandler::from_exchange_id(0),
+            1000,
+            1,
+            100,
+            ALICE
+        ));
+        assert_eq!(TokenModule::balance_of(&0, &ALICE), 10000 - 2000 + 998);
+    });
+}
+
+#[test]
+#[should_panic(expected = "Failed to add the genesis liquidity of the token 0")]
+fn genesis_exchanges_without_token_should_fail() {
+    let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+    pallet_balances::GenesisConfig::<Test> {
+        balances: vec![(ALICE, 10000)],
+    }
+    .assimilate_storage(&mut t)
+    .unwrap();
+    zenlink_assets::GenesisConfig::<Test> {
+        assets: vec![(ALICE, 1000, TEST_TOKEN.clone())],
+        balances: vec![],
+        allowances: vec![],
+    }
+    .assimilate_storage(&mut t)
+    .unwrap();
+
+    // The holder owns less token than the liquidity to add
+    let _ = crate::GenesisConfig::<Test> {
+        exchanges: vec![(0, 1000, 2000, ALICE)],
+    }
+    .assimilate_storage(&mut t);
+}
+
+fn put_opaque<K: Encode, V: Encode>(item: &[u8], key: K, value: V) {
+    put_storage_value(b"DexStorage", item, &Blake2_256::hash(&key.encode()), value);
+}
+
+#[test]
+fn migrate_to_v2_should_work() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(TokenModule::inner_issue(&ALICE, 10000, TEST_TOKEN), 0);
+        assert_eq!(TokenModule::inner_issue(&ALICE, 10000, TEST_OTHER_TOKEN), 1);
+        assert_ok!(TokenModule::inner_transfer(&0, &ALICE, &EXCHANGE_ACCOUNT, 2000));
+        Currency::make_free_balance_be(&EXCHANGE_ACCOUNT, 1000);
+
+        // The exchange of the first release hashed by `opaque_blake2_256`
+        put_opaque(b"Exchanges", 0u32, OldExchange { token_id: 0u32, liquidity_id: 1, account: EXCHANGE_ACCOUNT });
+        put_opaque(b"TokenToExchange", 0u32, 0u32);
+        put_opaque(b"ZLKToExchange", 1u32, 0u32);
+        crate::NextExchangeId::<Test>::put(1);
+
+        assert_eq!(DexModule::storage_version(), Releases::V1);
+        assert_eq!(DexModule::get_exchange(0), None);
+
+        DexModule::on_runtime_upgrade();
+
+        assert_eq!(DexModule::storage_version(), Releases::V2);
+        assert_eq!(DexModule::get_exchange(0), Some(Exchange {
+            token_id: 0,
+            liquidity_id: 1,
+            account: EXCHANGE_ACCOUNT,
+            fee_rate: None,
+            token_reserve: 2000,
+            currency_reserve: 1000,
+        }));
+        assert_eq!(DexModule::token_to_exchange(0), Some(0));
+        assert_eq!(DexModule::zlk_to_exchange(1), Some(0));
+        assert_eq!(DexModule::get_exchanges().len(), 1);
+
+        assert_eq!(
+            get_storage_value::<u32>(b"DexStorage", b"TokenToExchange", &Blake2_256::hash(&0u32.encode())),
+            None
+        );
+        assert_eq!(
+            get_storage_value::<Vec<u8>>(b"DexStorage", b"Exchanges", &Blake2_256::hash(&0u32.encode())),
+            None
+        );
+
+        // The migrated exchange works
+        assert_ok!(DexModule::currency_to_token_input(
+            Origin::signed(ALICE),
+            SwapHandler::from_exchange_id(0),
+            100,
+            1,
+            100,
+            ALICE
+        ));
+    })
+}
+
+#[test]
+fn migrate_to_v2_before_assets_should_work() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(TokenModule::inner_issue(&ALICE, 10000, TEST_TOKEN), 0);
+        assert_eq!(TokenModule::inner_issue(&ALICE, 10000, TEST_OTHER_TOKEN), 1);
+        Currency::make_free_balance_be(&EXCHANGE_ACCOUNT, 1000);
+
+        // The token balance of the exchange account keyed by the tuple, as the assets module
+        // of the first release left it
+        put_storage_value(
+            b"Assets",
+            b"Balances",
+            &Blake2_128Concat::hash(&(0u32, EXCHANGE_ACCOUNT).encode()),
+            2000u64,
+        );
+        assert_eq!(TokenModule::storage_version(), zenlink_assets::Releases::V1);
+        assert_eq!(TokenModule::balance_of(&0, &EXCHANGE_ACCOUNT), 0);
+
+        put_opaque(b"Exchanges", 0u32, OldExchange { token_id: 0u32, liquidity_id: 1, account: EXCHANGE_ACCOUNT });
+        put_opaque(b"TokenToExchange", 0u32, 0u32);
+        put_opaque(b"ZLKToExchange", 1u32, 0u32);
+        crate::NextExchangeId::<Test>::put(1);
+
+        // The dex module migrates before the assets module
+        DexModule::on_runtime_upgrade();
+        TokenModule::on_runtime_upgrade();
+
+        let exchange = DexModule::get_exchange(0).unwrap();
+        assert_eq!(exchange.token_reserve, 2000);
+        assert_eq!(exchange.currency_reserve, 1000);
+        assert_eq!(TokenModule::balance_of(&0, &EXCHANGE_ACCOUNT), 2000);
+
+        assert_ok!(DexModule::currency_to_token_input(
+            Origin::signed(ALICE),
+            SwapHandler::from_exchange_id(0),
+            100,
+            1,
+            100,
+            ALICE
+        ));
+    })
+}